@@ -0,0 +1,136 @@
+//! Symbol tables for biological sequence alphabets.
+
+/// Returns the nucleotides represented by an IUPAC nucleotide code, or `None` if the code is unknown.
+///
+/// Codes are case-insensitive and always expand to upper-case nucleotides. `U` is read as `T`.
+pub fn iupac_nucleotides(code: char)->Option<&'static [char]>{
+    let nucleotides: &'static [char] = match code.to_ascii_uppercase(){
+        'A' => &['A'],
+        'C' => &['C'],
+        'G' => &['G'],
+        'T' | 'U' => &['T'],
+        'R' => &['A', 'G'],
+        'Y' => &['C', 'T'],
+        'S' => &['C', 'G'],
+        'W' => &['A', 'T'],
+        'K' => &['G', 'T'],
+        'M' => &['A', 'C'],
+        'B' => &['C', 'G', 'T'],
+        'D' => &['A', 'G', 'T'],
+        'H' => &['A', 'C', 'T'],
+        'V' => &['A', 'C', 'G'],
+        'N' => &['A', 'C', 'G', 'T'],
+        _ => return None,
+    };
+    Some(nucleotides)
+}
+
+/// Checks if a character is an IUPAC amino acid code other than the unknown residue `X`.
+pub fn is_amino_acid(code: char)->bool{
+    code.is_ascii_alphabetic() && !code.eq_ignore_ascii_case(&'X')
+}
+
+/// Returns the complement of an IUPAC nucleotide code, or `None` if the code is unknown.
+///
+/// The case of the code is kept. `U` is complemented to `A`, and `A` always to `T`.
+pub fn iupac_complement(code: char)->Option<char>{
+    let complement = match code.to_ascii_uppercase(){
+        'A' => 'T',
+        'C' => 'G',
+        'G' => 'C',
+        'T' | 'U' => 'A',
+        'R' => 'Y',
+        'Y' => 'R',
+        'S' => 'S',
+        'W' => 'W',
+        'K' => 'M',
+        'M' => 'K',
+        'B' => 'V',
+        'D' => 'H',
+        'H' => 'D',
+        'V' => 'B',
+        'N' => 'N',
+        _ => return None,
+    };
+    match code.is_ascii_lowercase(){
+        true => Some(complement.to_ascii_lowercase()),
+        false => Some(complement),
+    }
+}
+
+/// Returns the reverse complement of a nucleotide sequence. Symbols that are not IUPAC nucleotide codes, such as gaps, are kept as they are.
+pub fn reverse_complement(sequence: &[char])->Vec<char>{
+    sequence.iter().rev().map(|code| iupac_complement(*code).unwrap_or(*code)).collect()
+}
+
+/// An NCBI genetic code table used to translate codons into amino acids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeneticCode{
+    /// Table 1.
+    Standard,
+    /// Table 2.
+    VertebrateMitochondrial,
+    /// Table 3.
+    YeastMitochondrial,
+    /// Table 4.
+    MoldMitochondrial,
+    /// Table 5.
+    InvertebrateMitochondrial,
+    /// Table 6.
+    CiliateNuclear,
+    /// Table 11.
+    Bacterial,
+}
+
+impl GeneticCode{
+    /// Returns the genetic code with the given NCBI table id, or `None` if the table is not supported.
+    pub fn from_table_id(table_id: usize)->Option<Self>{
+        match table_id{
+            1 => Some(GeneticCode::Standard),
+            2 => Some(GeneticCode::VertebrateMitochondrial),
+            3 => Some(GeneticCode::YeastMitochondrial),
+            4 => Some(GeneticCode::MoldMitochondrial),
+            5 => Some(GeneticCode::InvertebrateMitochondrial),
+            6 => Some(GeneticCode::CiliateNuclear),
+            11 => Some(GeneticCode::Bacterial),
+            _ => None,
+        }
+    }
+
+    /// Amino acids of the 64 codons in NCBI order, with the nucleotides of every position taken in the order `TCAG`.
+    fn amino_acids(&self)->&'static [u8; 64]{
+        match self{
+            GeneticCode::Standard | GeneticCode::Bacterial => b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+            GeneticCode::VertebrateMitochondrial => b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+            GeneticCode::YeastMitochondrial => b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+            GeneticCode::MoldMitochondrial => b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+            GeneticCode::InvertebrateMitochondrial => b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+            GeneticCode::CiliateNuclear => b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        }
+    }
+
+    /// Translates a codon into an amino acid, with `*` for stop codons.
+    ///
+    /// Codons with ambiguous IUPAC codes are translated if every nucleotide they represent gives the same amino acid, and to `X` otherwise.
+    pub fn translate_codon(&self, codon: &[char; 3])->char{
+        let nucleotides: Vec<&[char]> = match codon.iter().map(|code| iupac_nucleotides(*code)).collect::<Option<Vec<&[char]>>>(){
+            Some(nucleotides) => nucleotides,
+            None => return 'X',
+        };
+        let index = |nucleotide: &char| "TCAG".find(*nucleotide).expect("Nucleotides are upper-case");
+        let mut amino_acids = nucleotides[0].iter()
+            .flat_map(|first| nucleotides[1].iter().map(move |second| (first, second)))
+            .flat_map(|(first, second)| nucleotides[2].iter().map(move |third| 16*index(first)+4*index(second)+index(third)))
+            .map(|codon_index| self.amino_acids()[codon_index] as char);
+        let amino_acid = amino_acids.next().expect("Every code represents a nucleotide");
+        match amino_acids.all(|other| other==amino_acid){
+            true => amino_acid,
+            false => 'X',
+        }
+    }
+
+    /// Translates every complete codon of a nucleotide sequence.
+    pub fn translate(&self, sequence: &[char])->Vec<char>{
+        sequence.chunks_exact(3).map(|codon| self.translate_codon(&[codon[0], codon[1], codon[2]])).collect()
+    }
+}
@@ -1,16 +1,25 @@
 extern crate clap;
 
-use clap::{arg, Command};
+use clap::{arg, Arg, Command};
 use bio::io::fasta;
+use generalized_suffix_tree::alphabet::GeneticCode;
 use generalized_suffix_tree::data::tree_item::TreeItem;
 use generalized_suffix_tree::suffix_tree::KGST;
-use generalized_suffix_tree::suffix_tree::tree::SuffixTree;
+use generalized_suffix_tree::suffix_tree::frequency::Frequency;
+use generalized_suffix_tree::suffix_tree::unique::Uniqueness;
+use generalized_suffix_tree::suffix_tree::design::DesignConstraints;
+use generalized_suffix_tree::suffix_tree::export::{DotOptions, NetworkFormat};
+use generalized_suffix_tree::suffix_tree::matrix::NodeWeight;
+use generalized_suffix_tree::suffix_tree::similarity::Similarity;
+use generalized_suffix_tree::suffix_tree::cluster::{ClusterLink, ClusterMethod};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::fs::File;
-use std::{io::Write, fmt::Write as Otherwrite};
+use std::{io::{Write, BufWriter}, fmt::Write as Otherwrite};
+use itertools::Itertools;
 use std::path::PathBuf;
+use std::collections::HashSet;
 
-fn build_tree(file:&str, num_seq: &usize, max_depth: &usize)->KGST<char, String>{
+fn build_tree(file:&str, num_seq: &usize, max_depth: &usize, both_strands: bool, genetic_code: Option<GeneticCode>)->KGST<char, String>{
     println!("Building tree from {}", file);
     let reader = fasta::Reader::from_file(file).expect("File node found!");
 
@@ -43,7 +52,11 @@ fn build_tree(file:&str, num_seq: &usize, max_depth: &usize)->KGST<char, String>
         if seq.len()<=1{
             continue;
         }
-        tree.insert(result_data.id().to_string(), seq.to_vec(), max_depth);
+        match (genetic_code, both_strands){
+            (Some(genetic_code), _) => tree.insert_translated(result_data.id().to_string(), seq.to_vec(), genetic_code, max_depth),
+            (None, true) => tree.insert_both_strands(result_data.id().to_string(), seq.to_vec(), max_depth),
+            (None, false) => tree.insert(result_data.id().to_string(), seq.to_vec(), max_depth),
+        }
 
         pb.inc(1);   
         count+=1;
@@ -54,20 +67,16 @@ fn build_tree(file:&str, num_seq: &usize, max_depth: &usize)->KGST<char, String>
     tree
 }
 
-fn save_tree_edges(tree: &KGST<char, String>, output_path: String){
-    let edge_iter = tree.iter_edges_post();
+fn save_tree_network(tree: &KGST<char, String>, format: NetworkFormat, output_path: String){
     let mut outfile = PathBuf::from(output_path);
-    outfile.set_extension("network");
-    println!("Saving tree nodes to {}.", &outfile.to_string_lossy());
-    let pb = ProgressBar::new(edge_iter.len() as u64);
-    pb.set_style(ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-        .unwrap()
-        .progress_chars("#>-"));
-    let mut f = File::create(outfile).expect("Unable to create file");
-    for (n1, n2) in edge_iter{
-        writeln!(f, "{} {}", n1, n2).expect("Write failed");
-        pb.inc(1);
-    }
+    outfile.set_extension(match format{
+        NetworkFormat::GraphML => "graphml",
+        NetworkFormat::Gexf => "gexf",
+        NetworkFormat::NodeLinkJson => "json",
+    });
+    println!("Saving tree network to {}.", &outfile.to_string_lossy());
+    let mut f = BufWriter::new(File::create(outfile).expect("Unable to create file"));
+    tree.write_network(&mut f, format).expect("Write failed");
     println!("Saved");
 }
 
@@ -87,33 +96,278 @@ fn _save_tree(tree: &KGST<char, String>, output_path: String){
     println!("Saved");
 }
 
-fn node_sim(tree: &KGST<char, String>, output_path: String){
-    let string_iter = tree.iter_strings();
-    let pb = ProgressBar::new(string_iter.len() as u64);
-    pb.set_style(ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-        .unwrap()
-        .progress_chars("#>-"));
+fn save_tree_dot(tree: &KGST<char, String>, output_path: String){
+    println!("Saving tree to {}.", &output_path);
+    let mut f = BufWriter::new(File::create(output_path).expect("Unable to create file"));
+    tree.to_dot(&mut f, &DotOptions::default()).expect("Write failed");
+    println!("Saved");
+}
+
+fn save_tree_nodes_jsonl(tree: &KGST<char, String>, output_path: String){
+    println!("Saving tree nodes to {}.", &output_path);
+    let mut f = BufWriter::new(File::create(output_path).expect("Unable to create file"));
+    tree.write_nodes_jsonl(&mut f).expect("Write failed");
+    println!("Saved");
+}
+
+fn save_tree_newick(tree: &KGST<char, String>, output_path: String){
+    println!("Saving tree to {}.", &output_path);
+    let mut f = BufWriter::new(File::create(output_path).expect("Unable to create file"));
+    tree.to_newick(&mut f, false).expect("Write failed");
+    println!("Saved");
+}
+
+fn save_incidence_matrix(tree: &KGST<char, String>, weight: NodeWeight, csr: bool, output_path: String){
+    let incidence = tree.incidence_matrix(weight);
     let mut outfile = PathBuf::from(output_path);
-    outfile.set_extension("node_sim");
-    println!("Saving tree nodes to {}.", &outfile.to_string_lossy());
-    let mut f = File::create(outfile).expect("Unable to create file");
-    writeln!(f, "ID,node_values").expect("Write failed");
-    for (_itemid, (item, _depth)) in string_iter{
-        let mut node_values: Vec<u8> = vec![0; tree.num_nodes()];
-        for node_id in item.get_nodes(){
-            for path_node in tree.get_node_path_pre(node_id).iter(){
-                node_values[*path_node] = 1;
+    match csr{
+        true => {
+            outfile.set_extension("rows");
+            println!("Saving row ids to {}.", &outfile.to_string_lossy());
+            let mut f = BufWriter::new(File::create(&outfile).expect("Unable to create file"));
+            for row_id in incidence.row_ids(){
+                writeln!(f, "{}", row_id).expect("Write failed");
+            }
+            outfile.set_extension("csr");
+            println!("Saving incidence matrix to {}.", &outfile.to_string_lossy());
+            let mut f = BufWriter::new(File::create(outfile).expect("Unable to create file"));
+            incidence.write_csr(&mut f).expect("Write failed");
+        },
+        false => {
+            outfile.set_extension("mtx");
+            println!("Saving incidence matrix to {}.", &outfile.to_string_lossy());
+            let mut f = BufWriter::new(File::create(outfile).expect("Unable to create file"));
+            incidence.write_matrix_market(&mut f).expect("Write failed");
+        },
+    }
+    println!("Saved");
+}
+
+fn save_top_substrings(tree: &KGST<char, String>, k: &usize, min_len: &usize, max_len: &usize, frequency: Frequency, output_path: String){
+    println!("Saving top {} substrings to {}.", k, &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "substring\tcount\tstrings").expect("Write failed");
+    for frequent_substring in tree.top_k_substrings(*k, *min_len, *max_len, frequency){
+        writeln!(f, "{}\t{}\t{}", frequent_substring.substring.iter().collect::<String>(), frequent_substring.count, frequent_substring.strings.join(",")).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+fn save_kmers(tree: &KGST<char, String>, k: &usize, output_path: String){
+    println!("Saving {}-mers to {}.", k, &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "kmer\tcount\tstring_counts").expect("Write failed");
+    for kmer_count in tree.iter_kmers(*k){
+        let string_counts = kmer_count.string_counts.iter()
+            .map(|(item_id, count)| format!("{}:{}", item_id, count))
+            .sorted()
+            .join(",");
+        writeln!(f, "{}\t{}\t{}", kmer_count.kmer.iter().collect::<String>(), kmer_count.count, string_counts).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+/// Writes k-mers as the magic bytes `KGSTKMER`, k as a little-endian u32 and the number of records as a little-endian u64,
+/// followed by one record per k-mer made of its UTF-8 length as a u16, its UTF-8 bytes and its count as a u64.
+fn save_kmers_binary(tree: &KGST<char, String>, k: &usize, output_path: String){
+    println!("Saving {}-mers to {}.", k, &output_path);
+    let kmers: Vec<(String, usize)> = tree.iter_kmers(*k).map(|kmer_count| (kmer_count.kmer.iter().collect(), kmer_count.count)).collect();
+    let mut f = BufWriter::new(File::create(output_path).expect("Unable to create file"));
+    f.write_all(b"KGSTKMER").expect("Write failed");
+    f.write_all(&(*k as u32).to_le_bytes()).expect("Write failed");
+    f.write_all(&(kmers.len() as u64).to_le_bytes()).expect("Write failed");
+    for (kmer, count) in kmers{
+        f.write_all(&(kmer.len() as u16).to_le_bytes()).expect("Write failed");
+        f.write_all(kmer.as_bytes()).expect("Write failed");
+        f.write_all(&(count as u64).to_le_bytes()).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+fn save_kmer_spectrum(tree: &KGST<char, String>, k: &usize, output_path: String){
+    println!("Saving {}-mer spectrum to {}.", k, &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "count\tnum_kmers").expect("Write failed");
+    for (count, num_kmers) in tree.kmer_spectrum(*k){
+        writeln!(f, "{}\t{}", count, num_kmers).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+/// Writes the shortest unique substring length of every position as a bedGraph track, merging runs of equal lengths.
+fn save_shortest_unique_substrings(tree: &KGST<char, String>, uniqueness: Uniqueness, output_path: String){
+    println!("Saving shortest unique substrings to {}.", &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "track type=bedGraph name=shortest_unique_substrings").expect("Write failed");
+    for (item_id, lengths) in tree.shortest_unique_substrings(uniqueness).into_iter().sorted_by(|a, b| a.0.cmp(&b.0)){
+        let mut start = 0;
+        for run in lengths.chunk_by(|len_1, len_2| len_1==len_2){
+            let end = start+run.len();
+            if let Some(len) = run[0]{
+                writeln!(f, "{}\t{}\t{}\t{}", item_id, start, end, len).expect("Write failed");
             }
+            start = end;
         }
-        writeln!(f, "{},{}", item.get_id(), node_values.iter().fold(String::new(), |mut output, i| {
-            let _  = write!(output, "{i}");
-            output
-        })).expect("Write failed");
-        pb.inc(1);
     }
     println!("Saved");
 }
 
+fn save_distinguishing_substrings(tree: &KGST<char, String>, targets: &[String], background: &[String], min_target_fraction: &f64, max_background: &usize, output_path: String){
+    println!("Saving distinguishing substrings to {}.", &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "substring\ttarget_count\tbackground_count\ttargets").expect("Write failed");
+    for distinguishing in tree.distinguishing_substrings(targets, background, *min_target_fraction, *max_background){
+        writeln!(f, "{}\t{}\t{}\t{}", distinguishing.substring.iter().collect::<String>(), distinguishing.target_count, distinguishing.background_count, distinguishing.targets.join(",")).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+/// Writes primer and probe candidates as a tsv ranked from best to worst.
+fn save_design_candidates(tree: &KGST<char, String>, targets: &[String], off_targets: &[String], constraints: &DesignConstraints, output_path: String){
+    println!("Saving design candidates to {}.", &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "rank\tsequence\tlength\tgc_content\tmelting_temperature\tlongest_homopolymer\ttarget_occurrences\ttargets").expect("Write failed");
+    for (rank, candidate) in tree.design_candidates(targets, off_targets, constraints).into_iter().enumerate(){
+        writeln!(f, "{}\t{}\t{}\t{:.3}\t{:.1}\t{}\t{}\t{}", rank+1, candidate.sequence, candidate.sequence.len(), candidate.gc_content, candidate.melting_temperature, candidate.longest_homopolymer, candidate.occurrences, candidate.targets.join(",")).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+fn save_tandem_repeats(tree: &KGST<char, String>, min_period: &usize, max_period: &usize, min_copies: &usize, output_path: String){
+    println!("Saving tandem repeats to {}.", &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "string_id\tstart\tperiod\tcopies").expect("Write failed");
+    for repeat in tree.tandem_repeats(*min_period, *max_period, *min_copies){
+        writeln!(f, "{}\t{}\t{}\t{}", repeat.string, repeat.start, repeat.period, repeat.copies).expect("Write failed");
+    }
+    println!("Saved");
+}
+
+fn save_similarity_matrix(tree: &KGST<char, String>, ids: &[String], similarity: Similarity, weight: NodeWeight, phylip: bool, output_path: String){
+    println!("Saving similarity matrix to {}.", &output_path);
+    let similarities = tree.similarity_matrix(ids, similarity, weight);
+    let mut f = BufWriter::new(File::create(output_path).expect("Unable to create file"));
+    match phylip{
+        true => similarities.to_distances().write_phylip(&mut f).expect("Write failed"),
+        false => similarities.write_tsv(&mut f).expect("Write failed"),
+    }
+    println!("Saved");
+}
+
+/// Writes the pairwise alignment-free distances as a PHYLIP distance matrix.
+fn save_distance_matrix(tree: &KGST<char, String>, method: &str, k: &usize, mismatches: &usize, output_path: String){
+    println!("Saving distance matrix to {}.", &output_path);
+    let distances = match method{
+        "spectrum" => tree.spectrum_kernel(*k).kernel_distances(),
+        "mismatch" => tree.mismatch_kernel(*k, *mismatches).kernel_distances(),
+        _ => tree.acs_distances(),
+    };
+    let mut f = BufWriter::new(File::create(output_path).expect("Unable to create file"));
+    distances.write_phylip(&mut f).expect("Write failed");
+    println!("Saved");
+}
+
+/// Writes the substring count vectors in libsvm or binary CSR format, and the feature dictionary next to them.
+fn save_feature_vectors(tree: &KGST<char, String>, min_len: &usize, max_len: &usize, min_support: &usize, csr: bool, output_path: String){
+    println!("Saving feature vectors to {}.", &output_path);
+    let vectors = tree.feature_vectors(*min_len, *max_len, *min_support);
+    let mut f = BufWriter::new(File::create(&output_path).expect("Unable to create file"));
+    match csr{
+        true => vectors.counts().write_csr(&mut f).expect("Write failed"),
+        false => vectors.write_libsvm(&mut f).expect("Write failed"),
+    }
+    let mut outfile = PathBuf::from(output_path);
+    outfile.set_extension("features");
+    println!("Saving feature dictionary to {}.", &outfile.to_string_lossy());
+    let mut f = BufWriter::new(File::create(outfile).expect("Unable to create file"));
+    vectors.write_features(&mut f).expect("Write failed");
+    println!("Saved");
+}
+
+/// Writes the cluster of every sequence as a tsv.
+fn save_clusters(tree: &KGST<char, String>, link: ClusterLink, method: ClusterMethod, output_path: String){
+    println!("Saving clusters to {}.", &output_path);
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "id\tcluster\trepresentative").expect("Write failed");
+    for (cluster_idx, cluster) in tree.cluster(link, method).into_iter().enumerate(){
+        for member in cluster.members.iter(){
+            writeln!(f, "{}\t{}\t{}", member, cluster_idx, cluster.representative).expect("Write failed");
+        }
+    }
+    println!("Saved");
+}
+
+/// Writes the duplicate and contained sequences as a tsv, and optionally the remaining indexed sequences as a fasta file.
+fn save_containment_report(tree: &KGST<char, String>, source: &str, dedup_path: Option<&String>, output_path: String){
+    println!("Saving containment report to {}.", &output_path);
+    let report = tree.containment_report();
+    let mut f = File::create(output_path).expect("Unable to create file");
+    writeln!(f, "id\trelation\tother_id\toffset").expect("Write failed");
+    for group in report.duplicates.iter(){
+        for duplicate in group.iter().skip(1){
+            writeln!(f, "{}\tduplicate\t{}\t0", duplicate, group[0]).expect("Write failed");
+        }
+    }
+    for (contained, container, offset) in report.contained.iter(){
+        writeln!(f, "{}\tcontained\t{}\t{}", contained, container, offset).expect("Write failed");
+    }
+    if let Some(dedup_path) = dedup_path{
+        println!("Saving deduplicated sequences to {}.", dedup_path);
+        let redundant = report.redundant_ids();
+        let indexed: HashSet<&String> = tree.iter_strings().map(|(_, (item, _))| item.get_id()).collect();
+        let mut writer = fasta::Writer::to_file(dedup_path).expect("Unable to create file");
+        for record in fasta::Reader::from_file(source).expect("File node found!").records(){
+            let record = record.unwrap();
+            let id = record.id().to_string();
+            if indexed.contains(&id) && !redundant.contains(&id){
+                writer.write_record(&record).expect("Write failed");
+            }
+        }
+    }
+    println!("Saved");
+}
+
+/// Parses the weight of incidence matrix entries from its command line name.
+fn node_weight(name: &str)->NodeWeight{
+    match name{
+        "occurrences" => NodeWeight::Occurrences,
+        "edge-length" => NodeWeight::EdgeLength,
+        _ => NodeWeight::Binary,
+    }
+}
+
+/// Arguments shared by every subcommand that builds a tree from a fasta file.
+fn tree_args()->[Arg; 3]{
+    [
+        arg!(-s --source <SRC_FILE> "Source file with sequences(fasta)")
+            .required(true),
+        arg!(-d --depth <MAX_DEPTH> "max depth of output tree. (0==length of longest string)")
+            .required(true)
+            .value_parser(clap::value_parser!(usize)),
+        arg!(-n --num <NUM_SEQ> "Number of seq. (0==all)")
+            .required(true)
+            .value_parser(clap::value_parser!(usize)),
+    ]
+}
+
+fn parse_genetic_code(table: &str)->Result<GeneticCode, String>{
+    table.parse::<usize>().ok()
+        .and_then(GeneticCode::from_table_id)
+        .ok_or(format!("unsupported genetic code table {}, expected one of 1, 2, 3, 4, 5, 6 or 11", table))
+}
+
+fn build_tree_from_args(sub_m: &clap::ArgMatches)->KGST<char, String>{
+    build_tree(
+        sub_m.get_one::<String>("source").expect("required").as_str(), 
+        sub_m.get_one::<usize>("num").expect("required"), 
+        sub_m.get_one::<usize>("depth").expect("required"),
+        sub_m.try_get_one::<bool>("both-strands").ok().flatten().is_some_and(|both_strands| *both_strands),
+        match sub_m.try_get_one::<bool>("translate").ok().flatten().is_some_and(|translate| *translate){
+            true => Some(*sub_m.get_one::<GeneticCode>("code").expect("default")),
+            false => None,
+        }
+    )
+}
 
 fn main(){
     let matches = Command::new("Generalized suffix tree")
@@ -121,46 +375,474 @@ fn main(){
         .author("Sriram Vijendran <vijendran.sriram@gmail.com>")
         .subcommand(Command::new("build")
             .about("Build suffix tree index from reference fasta file")
-            .arg(arg!(-s --source <SRC_FILE> "Source file with sequences(fasta)")
+            .args(tree_args())
+            .arg(arg!(--network "Export tree edges and suffix links as a labelled network")
+                .required(false)
+                .value_parser(clap::value_parser!(bool))
+                )
+            .arg(arg!(--"network-format" <FORMAT> "File format of the exported network")
+                .required(false)
+                .default_value("graphml")
+                .value_parser(["graphml", "gexf", "json"])
+                )
+            .arg(arg!(--sim "Export the sparse string-by-node incidence matrix")
+                .required(false)
+                .value_parser(clap::value_parser!(bool))
+                )
+            .arg(arg!(--"sim-weight" <WEIGHT> "Weight of the incidence matrix entries")
+                .required(false)
+                .default_value("binary")
+                .value_parser(["binary", "occurrences", "edge-length"])
+                )
+            .arg(arg!(--"sim-format" <FORMAT> "File format of the incidence matrix")
+                .required(false)
+                .default_value("mtx")
+                .value_parser(["mtx", "csr"])
+                )
+            .arg(arg!(--dot <DOT_FILE> "Export the tree as a Graphviz digraph")
+                .required(false)
+                )
+            .arg(arg!(--jsonl <JSONL_FILE> "Export every node as a JSON Lines record")
+                .required(false)
+                )
+            .arg(arg!(--newick <NEWICK_FILE> "Export the tree in Newick format")
+                .required(false)
+                )
+            .arg(arg!(--"both-strands" "Also index the reverse complement of every sequence")
+                .required(false)
+                .value_parser(clap::value_parser!(bool))
+                )
+            .arg(arg!(--translate "Index the six-frame protein translation of every sequence")
+                .required(false)
+                .value_parser(clap::value_parser!(bool))
+                )
+            .arg(arg!(--code <TABLE> "NCBI genetic code table used with --translate")
+                .required(false)
+                .default_value("1")
+                .value_parser(parse_genetic_code)
+                )
+        )
+        .subcommand(Command::new("top")
+            .about("Find the most frequent substrings within a length range")
+            .args(tree_args())
+            .arg(arg!(-k --k <K> "Number of substrings to report")
                 .required(true)
+                .value_parser(clap::value_parser!(usize))
                 )
-            .arg(arg!(-d --depth <MAX_DEPTH> "max depth of output tree. (0==length of longest string)")
+            .arg(arg!(--min <MIN_LEN> "Minimum substring length")
                 .required(true)
                 .value_parser(clap::value_parser!(usize))
                 )
-            .arg(arg!(-n --num <NUM_SEQ> "Number of seq. (0==all)")
+            .arg(arg!(--max <MAX_LEN> "Maximum substring length")
                 .required(true)
                 .value_parser(clap::value_parser!(usize))
                 )
-            .arg(arg!(--network "Export edges as network topology")
+            .arg(arg!(--strings "Count distinct strings instead of occurrences")
                 .required(false)
                 .value_parser(clap::value_parser!(bool))
                 )
-            .arg(arg!(--sim "Export node values per string")
+            .arg(arg!(-o --out <OUT_FILE> "Output file (tsv)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("kmers")
+            .about("Count the k-mers of a fasta file")
+            .args(tree_args())
+            .arg(arg!(-k --k <K> "Length of k-mers")
+                .required(true)
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--spectrum "Write the k-mer spectrum (count, number of k-mers) instead of the k-mers")
                 .required(false)
                 .value_parser(clap::value_parser!(bool))
                 )
+            .arg(arg!(--binary "Write k-mers and counts in binary format instead of tsv")
+                .required(false)
+                .value_parser(clap::value_parser!(bool))
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("sus")
+            .about("Find the shortest unique substring starting at every position (bedGraph)")
+            .args(tree_args())
+            .arg(arg!(--"per-string" "Only require substrings to be unique within their own string")
+                .required(false)
+                .value_parser(clap::value_parser!(bool))
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (bedGraph)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("distinguish")
+            .about("Find minimal substrings that occur in most target strings and few background strings")
+            .args(tree_args())
+            .arg(arg!(--targets <TARGET_IDS> "Comma separated ids of the target strings")
+                .required(true)
+                .value_delimiter(',')
+                )
+            .arg(arg!(--background <BACKGROUND_IDS> "Comma separated ids of the background strings")
+                .required(true)
+                .value_delimiter(',')
+                )
+            .arg(arg!(-p --"min-fraction" <MIN_FRACTION> "Minimum fraction of targets containing a substring")
+                .required(false)
+                .default_value("1.0")
+                .value_parser(clap::value_parser!(f64))
+                )
+            .arg(arg!(-q --"max-background" <MAX_BACKGROUND> "Maximum number of background strings containing a substring")
+                .required(false)
+                .default_value("0")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (tsv)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("design")
+            .about("Design primer and probe candidates shared by all targets and absent from off-targets (ranked tsv)")
+            .args(tree_args())
+            .arg(arg!(--targets <TARGET_IDS> "Comma separated ids of the target strings (default: all strings that are not off-targets)")
+                .required(false)
+                .value_delimiter(',')
+                )
+            .arg(arg!(--"off-targets" <OFF_TARGET_IDS> "Comma separated ids of the off-target strings")
+                .required(false)
+                .value_delimiter(',')
+                )
+            .arg(arg!(--"min-len" <MIN_LEN> "Minimum candidate length")
+                .required(false)
+                .default_value("18")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"max-len" <MAX_LEN> "Maximum candidate length")
+                .required(false)
+                .default_value("25")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"min-gc" <MIN_GC> "Minimum GC content (fraction)")
+                .required(false)
+                .default_value("0.4")
+                .value_parser(clap::value_parser!(f64))
+                )
+            .arg(arg!(--"max-gc" <MAX_GC> "Maximum GC content (fraction)")
+                .required(false)
+                .default_value("0.6")
+                .value_parser(clap::value_parser!(f64))
+                )
+            .arg(arg!(--"min-tm" <MIN_TM> "Minimum melting temperature (Celsius)")
+                .required(false)
+                .default_value("52")
+                .value_parser(clap::value_parser!(f64))
+                )
+            .arg(arg!(--"max-tm" <MAX_TM> "Maximum melting temperature (Celsius)")
+                .required(false)
+                .default_value("65")
+                .value_parser(clap::value_parser!(f64))
+                )
+            .arg(arg!(--"max-homopolymer" <MAX_HOMOPOLYMER> "Longest allowed run of a single nucleotide")
+                .required(false)
+                .default_value("4")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (tsv)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("tandem")
+            .about("Find maximal tandem repeats in every sequence")
+            .args(tree_args())
+            .arg(arg!(--"min-period" <MIN_PERIOD> "Minimum period (length of the repeated unit)")
+                .required(false)
+                .default_value("1")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"max-period" <MAX_PERIOD> "Maximum period (0==no limit)")
+                .required(false)
+                .default_value("0")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"min-copies" <MIN_COPIES> "Minimum number of full copies of the unit")
+                .required(false)
+                .default_value("2")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (tsv)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("similarity")
+            .about("Compute pairwise similarities of sequences from shared tree nodes")
+            .args(tree_args())
+            .arg(arg!(--ids <IDS> "Comma separated ids of the compared strings (default: all strings)")
+                .required(false)
+                .value_delimiter(',')
+                )
+            .arg(arg!(--measure <MEASURE> "Similarity measure")
+                .required(false)
+                .default_value("jaccard")
+                .value_parser(["jaccard", "weighted-jaccard", "cosine"])
+                )
+            .arg(arg!(--weight <WEIGHT> "Weight of the shared nodes")
+                .required(false)
+                .default_value("edge-length")
+                .value_parser(["binary", "occurrences", "edge-length"])
+                )
+            .arg(arg!(--format <FORMAT> "Output format, similarities as tsv or 1-similarity as a PHYLIP distance matrix")
+                .required(false)
+                .default_value("tsv")
+                .value_parser(["tsv", "phylip"])
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("distance")
+            .about("Compute alignment-free distances of sequences (PHYLIP distance matrix)")
+            .args(tree_args())
+            .arg(arg!(--method <METHOD> "Average common substring distance, or distance between normalized spectrum or mismatch kernel features")
+                .required(false)
+                .default_value("acs")
+                .value_parser(["acs", "spectrum", "mismatch"])
+                )
+            .arg(arg!(-k --kmer <K> "k-mer length of the kernels")
+                .required(false)
+                .default_value("8")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(-m --mismatches <MISMATCHES> "Number of mismatches allowed by the mismatch kernel")
+                .required(false)
+                .default_value("1")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (phylip)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("features")
+            .about("Export substring count feature vectors of every sequence (libsvm or csr)")
+            .args(tree_args())
+            .arg(arg!(--"min-len" <MIN_LEN> "Minimum substring length")
+                .required(false)
+                .default_value("1")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"max-len" <MAX_LEN> "Maximum substring length")
+                .required(true)
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"min-support" <MIN_SUPPORT> "Minimum number of sequences containing a substring")
+                .required(false)
+                .default_value("2")
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--format <FORMAT> "Output format of the count vectors")
+                .required(false)
+                .default_value("libsvm")
+                .value_parser(["libsvm", "csr"])
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file, the feature dictionary is written next to it (.features)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("cluster")
+            .about("Cluster sequences that share substrings or k-mers (tsv)")
+            .args(tree_args())
+            .arg(arg!(-k --kmer <K> "Minimum length of a shared substring, or k-mer length with --min-fraction")
+                .required(true)
+                .value_parser(clap::value_parser!(usize))
+                )
+            .arg(arg!(--"min-fraction" <MIN_FRACTION> "Link sequences sharing this fraction of the k-mers of the one with fewer k-mers")
+                .required(false)
+                .value_parser(clap::value_parser!(f64))
+                )
+            .arg(arg!(--method <METHOD> "Clustering method")
+                .required(false)
+                .default_value("single")
+                .value_parser(["single", "greedy"])
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (tsv)")
+                .required(true)
+                )
+        )
+        .subcommand(Command::new("containment")
+            .about("Report sequences that are duplicates of or contained in other sequences (tsv)")
+            .args(tree_args())
+            .arg(arg!(--dedup <FASTA_FILE> "Also write the indexed sequences that are neither contained nor a repeated duplicate")
+                .required(false)
+                )
+            .arg(arg!(-o --out <OUT_FILE> "Output file (tsv)")
+                .required(true)
+                )
         )
         .about("CLI tool to build and serialize K-Truncated Generalized Suffix trees")
         .get_matches();
 
         match matches.subcommand(){
             Some(("build",  sub_m)) => {
-                let tree: KGST<char, String> = build_tree(
-                    sub_m.get_one::<String>("source").expect("required").as_str(), 
-                    sub_m.get_one::<usize>("num").expect("required"), 
-                    sub_m.get_one::<usize>("depth").expect("required")
-                );
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
                 if sub_m.get_flag("network"){
-                    save_tree_edges(&tree, sub_m.get_one::<String>("source").expect("required").to_string());
+                    let format = match sub_m.get_one::<String>("network-format").expect("default").as_str(){
+                        "gexf" => NetworkFormat::Gexf,
+                        "json" => NetworkFormat::NodeLinkJson,
+                        _ => NetworkFormat::GraphML,
+                    };
+                    save_tree_network(&tree, format, sub_m.get_one::<String>("source").expect("required").to_string());
                 }
                 if sub_m.get_flag("sim"){
-                    node_sim(&tree, sub_m.get_one::<String>("source").expect("required").to_string());
+                    let weight = node_weight(sub_m.get_one::<String>("sim-weight").expect("default"));
+                    let csr = sub_m.get_one::<String>("sim-format").expect("default")=="csr";
+                    save_incidence_matrix(&tree, weight, csr, sub_m.get_one::<String>("source").expect("required").to_string());
+                }
+                if let Some(dot_path) = sub_m.get_one::<String>("dot"){
+                    save_tree_dot(&tree, dot_path.to_string());
+                }
+                if let Some(jsonl_path) = sub_m.get_one::<String>("jsonl"){
+                    save_tree_nodes_jsonl(&tree, jsonl_path.to_string());
+                }
+                if let Some(newick_path) = sub_m.get_one::<String>("newick"){
+                    save_tree_newick(&tree, newick_path.to_string());
                 }
                 // else{
                 //     save_tree(&tree, sub_m.get_one::<String>("out").expect("required").to_string());
                 // }
             },
+            Some(("top",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let frequency = match sub_m.get_flag("strings"){
+                    true => Frequency::Strings,
+                    false => Frequency::Occurrences,
+                };
+                save_top_substrings(
+                    &tree, 
+                    sub_m.get_one::<usize>("k").expect("required"), 
+                    sub_m.get_one::<usize>("min").expect("required"), 
+                    sub_m.get_one::<usize>("max").expect("required"), 
+                    frequency, 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
+            Some(("kmers",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let k = sub_m.get_one::<usize>("k").expect("required");
+                let output_path = sub_m.get_one::<String>("out").expect("required").to_string();
+                if sub_m.get_flag("spectrum"){
+                    save_kmer_spectrum(&tree, k, output_path);
+                }
+                else if sub_m.get_flag("binary"){
+                    save_kmers_binary(&tree, k, output_path);
+                }
+                else{
+                    save_kmers(&tree, k, output_path);
+                }
+            },
+            Some(("sus",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let uniqueness = match sub_m.get_flag("per-string"){
+                    true => Uniqueness::String,
+                    false => Uniqueness::Collection,
+                };
+                save_shortest_unique_substrings(&tree, uniqueness, sub_m.get_one::<String>("out").expect("required").to_string());
+            },
+            Some(("distinguish",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let targets: Vec<String> = sub_m.get_many::<String>("targets").expect("required").cloned().collect();
+                let background: Vec<String> = sub_m.get_many::<String>("background").expect("required").cloned().collect();
+                save_distinguishing_substrings(
+                    &tree, 
+                    &targets, 
+                    &background, 
+                    sub_m.get_one::<f64>("min-fraction").expect("default"), 
+                    sub_m.get_one::<usize>("max-background").expect("default"), 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
+            Some(("design",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let targets: Vec<String> = sub_m.get_many::<String>("targets").map(|ids| ids.cloned().collect()).unwrap_or_default();
+                let off_targets: Vec<String> = sub_m.get_many::<String>("off-targets").map(|ids| ids.cloned().collect()).unwrap_or_default();
+                let constraints = DesignConstraints {
+                    min_len: *sub_m.get_one::<usize>("min-len").expect("default"),
+                    max_len: *sub_m.get_one::<usize>("max-len").expect("default"),
+                    min_gc: *sub_m.get_one::<f64>("min-gc").expect("default"),
+                    max_gc: *sub_m.get_one::<f64>("max-gc").expect("default"),
+                    min_tm: *sub_m.get_one::<f64>("min-tm").expect("default"),
+                    max_tm: *sub_m.get_one::<f64>("max-tm").expect("default"),
+                    max_homopolymer: *sub_m.get_one::<usize>("max-homopolymer").expect("default"),
+                };
+                save_design_candidates(&tree, &targets, &off_targets, &constraints, sub_m.get_one::<String>("out").expect("required").to_string());
+            },
+            Some(("tandem",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                save_tandem_repeats(
+                    &tree, 
+                    sub_m.get_one::<usize>("min-period").expect("default"), 
+                    sub_m.get_one::<usize>("max-period").expect("default"), 
+                    sub_m.get_one::<usize>("min-copies").expect("default"), 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
+            Some(("similarity",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let ids: Vec<String> = sub_m.get_many::<String>("ids").map(|ids| ids.cloned().collect()).unwrap_or_default();
+                let similarity = match sub_m.get_one::<String>("measure").expect("default").as_str(){
+                    "weighted-jaccard" => Similarity::WeightedJaccard,
+                    "cosine" => Similarity::Cosine,
+                    _ => Similarity::Jaccard,
+                };
+                save_similarity_matrix(
+                    &tree, 
+                    &ids, 
+                    similarity, 
+                    node_weight(sub_m.get_one::<String>("weight").expect("default")), 
+                    sub_m.get_one::<String>("format").expect("default")=="phylip", 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
+            Some(("distance",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                save_distance_matrix(
+                    &tree, 
+                    sub_m.get_one::<String>("method").expect("default"), 
+                    sub_m.get_one::<usize>("kmer").expect("default"), 
+                    sub_m.get_one::<usize>("mismatches").expect("default"), 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
+            Some(("features",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                save_feature_vectors(
+                    &tree, 
+                    sub_m.get_one::<usize>("min-len").expect("default"), 
+                    sub_m.get_one::<usize>("max-len").expect("required"), 
+                    sub_m.get_one::<usize>("min-support").expect("default"), 
+                    sub_m.get_one::<String>("format").expect("default")=="csr", 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
+            Some(("cluster",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                let k = *sub_m.get_one::<usize>("kmer").expect("required");
+                let link = match sub_m.get_one::<f64>("min-fraction"){
+                    Some(min_fraction) => ClusterLink::SharedKmers { k, min_fraction: *min_fraction },
+                    None => ClusterLink::SharedSubstring(k),
+                };
+                let method = match sub_m.get_one::<String>("method").expect("default").as_str(){
+                    "greedy" => ClusterMethod::GreedyCentroid,
+                    _ => ClusterMethod::SingleLinkage,
+                };
+                save_clusters(&tree, link, method, sub_m.get_one::<String>("out").expect("required").to_string());
+            },
+            Some(("containment",  sub_m)) => {
+                let tree: KGST<char, String> = build_tree_from_args(sub_m);
+                save_containment_report(
+                    &tree, 
+                    sub_m.get_one::<String>("source").expect("required"), 
+                    sub_m.get_one::<String>("dedup"), 
+                    sub_m.get_one::<String>("out").expect("required").to_string()
+                );
+            },
             _ => {
                 println!("No option selected! Refer help page (-h flag)");
             }
@@ -1,4 +1,5 @@
 pub mod tree_item;
+pub mod pattern;
 
 use std::fmt;
 use core::fmt::{Debug, Display};
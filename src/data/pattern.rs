@@ -0,0 +1,386 @@
+use crate::alphabet::{iupac_nucleotides, is_amino_acid};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use core::fmt::{Debug, Display};
+
+/// Errors raised while parsing a pattern from its text representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError{
+    /// A symbol that is not part of the pattern alphabet, along with its position in the input.
+    UnknownSymbol(char, usize),
+    /// A pattern element that could not be parsed.
+    MalformedElement(String),
+    /// The pattern does not contain any symbols.
+    Empty,
+    /// A gap whose minimum length is larger than its maximum length.
+    InvalidGap(usize, usize),
+}
+
+impl Display for PatternError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self{
+            PatternError::UnknownSymbol(c, pos) => write!(f, "unknown symbol '{}' at position {}", c, pos),
+            PatternError::MalformedElement(e) => write!(f, "malformed pattern element '{}'", e),
+            PatternError::Empty => write!(f, "pattern is empty"),
+            PatternError::InvalidGap(min, max) => write!(f, "gap minimum {} is larger than maximum {}", min, max),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// A single position of a degenerate pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternSymbol<T>{
+    /// Matches any symbol.
+    Any,
+    /// Matches any of the listed symbols.
+    OneOf(Vec<T>),
+    /// Matches any symbol except the listed ones.
+    NoneOf(Vec<T>),
+}
+
+impl<T> PatternSymbol<T>
+where
+    T: PartialEq
+{
+    /// Checks if a symbol is allowed at this position.
+    pub fn matches(&self, c: &T)->bool{
+        match self{
+            PatternSymbol::Any => true,
+            PatternSymbol::OneOf(allowed) => allowed.contains(c),
+            PatternSymbol::NoneOf(excluded) => !excluded.contains(c),
+        }
+    }
+}
+
+impl<T> Display for PatternSymbol<T>
+where
+    T: Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self{
+            PatternSymbol::Any => write!(f, "x"),
+            PatternSymbol::OneOf(allowed) if allowed.len()==1 => write!(f, "{}", allowed[0]),
+            PatternSymbol::OneOf(allowed) => {
+                write!(f, "[")?;
+                for c in allowed{
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "]")
+            },
+            PatternSymbol::NoneOf(excluded) => {
+                write!(f, "{{")?;
+                for c in excluded{
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+/// A fixed length pattern in which every position is a set of allowed symbols.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pattern<T>{
+    symbols: Vec<PatternSymbol<T>>,
+}
+
+impl<T> Pattern<T>
+where
+    T: PartialEq + Clone
+{
+    /// Creates a pattern from its symbols. Empty patterns would match everywhere, so they are rejected.
+    pub fn new(symbols: Vec<PatternSymbol<T>>)->Result<Self, PatternError>{
+        match symbols.is_empty(){
+            true => Err(PatternError::Empty),
+            false => Ok(Self { symbols }),
+        }
+    }
+
+    /// Creates a pattern that only matches the input slice.
+    pub fn exact(s: &[T])->Result<Self, PatternError>{
+        Self::new(s.iter().map(|c| PatternSymbol::OneOf(vec![c.clone()])).collect())
+    }
+
+    pub fn symbols(&self)->&[PatternSymbol<T>]{
+        &self.symbols
+    }
+
+    pub fn len(&self)->usize{
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.symbols.is_empty()
+    }
+
+    /// Checks if the pattern matches the input slice over its entire length.
+    pub fn matches(&self, s: &[T])->bool{
+        s.len()==self.len() && self.symbols.iter().zip(s.iter()).all(|(symbol, c)| symbol.matches(c))
+    }
+}
+
+impl Pattern<char>{
+    /// Parses a nucleotide pattern written with IUPAC codes, such as `ACGTNRY`.
+    ///
+    /// Codes are case-insensitive, but they always stand for upper-case nucleotides, so lower-case (soft-masked) sequences are not matched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::data::pattern::Pattern;
+    ///
+    /// let pattern = Pattern::from_iupac("GAATTCN").unwrap();
+    /// assert!(pattern.matches(&"GAATTCA".chars().collect::<Vec<char>>()));
+    /// ```
+    pub fn from_iupac(s: &str)->Result<Self, PatternError>{
+        Self::from_iupac_at(s, 0)
+    }
+
+    /// Parses IUPAC codes that start at character `offset` of a longer pattern, so unknown symbols are reported at their position in it.
+    fn from_iupac_at(s: &str, offset: usize)->Result<Self, PatternError>{
+        let symbols = s.chars().enumerate().map(|(pos, c)| {
+            match iupac_nucleotides(c){
+                None => Err(PatternError::UnknownSymbol(c, offset+pos)),
+                Some(nucleotides) => Ok(PatternSymbol::OneOf(nucleotides.to_vec())),
+            }
+        }).collect::<Result<Vec<PatternSymbol<char>>, PatternError>>()?;
+        Self::new(symbols)
+    }
+
+    /// Parses a PROSITE-like protein pattern, such as `C-x(2)-[ST]-{P}-H`.
+    ///
+    /// Elements are separated by `-` and may be a single residue, `x` for any residue, `[...]` for a set of allowed residues or `{...}` for a set of excluded residues.
+    /// Each element may be followed by a fixed repeat count such as `x(3)`. A trailing `.` is ignored.
+    /// Variable length elements such as `x(2,4)` are only supported by [`GappedPattern`].
+    pub fn from_prosite(s: &str)->Result<Self, PatternError>{
+        let mut symbols: Vec<PatternSymbol<char>> = vec![];
+        for (offset, element) in split_elements(s.trim_end().trim_end_matches('.')){
+            let (symbol, repeats) = parse_prosite_element(element, offset)?;
+            for _ in 0..repeats{
+                symbols.push(symbol.clone());
+            }
+        }
+        Self::new(symbols)
+    }
+}
+
+/// Splits a pattern into its `-` separated elements, along with the character offset of each element in the pattern.
+fn split_elements(s: &str)->impl Iterator<Item = (usize, &str)>{
+    let mut offset = 0;
+    s.split('-').map(move |element| {
+        let start = offset;
+        offset += element.chars().count()+1;
+        (start, element)
+    })
+}
+
+/// Parses a single PROSITE element that starts at character `offset` of the pattern into a symbol and its repeat count.
+fn parse_prosite_element(element: &str, offset: usize)->Result<(PatternSymbol<char>, usize), PatternError>{
+    let malformed = || PatternError::MalformedElement(element.to_string());
+    let offset = offset+element.chars().take_while(|c| c.is_whitespace()).count();
+    let element = element.trim();
+    let (body, repeats) = match element.find('('){
+        None => (element, 1),
+        Some(idx) => {
+            let count = element[idx..].strip_prefix('(').and_then(|x| x.strip_suffix(')')).ok_or_else(malformed)?;
+            (&element[..idx], count.trim().parse::<usize>().map_err(|_| malformed())?)
+        },
+    };
+    let residues = |inner: &str, inner_offset: usize| -> Result<Vec<char>, PatternError> {
+        inner.chars().enumerate().map(|(pos, c)| match is_amino_acid(c){
+            true => Ok(c.to_ascii_uppercase()),
+            false => Err(PatternError::UnknownSymbol(c, offset+inner_offset+pos)),
+        }).collect()
+    };
+    let symbol = if body.eq_ignore_ascii_case("x"){
+        PatternSymbol::Any
+    }
+    else if let Some(inner) = body.strip_prefix('[').and_then(|x| x.strip_suffix(']')){
+        PatternSymbol::OneOf(residues(inner, 1)?)
+    }
+    else if let Some(inner) = body.strip_prefix('{').and_then(|x| x.strip_suffix('}')){
+        PatternSymbol::NoneOf(residues(inner, 1)?)
+    }
+    else if body.chars().count()==1{
+        PatternSymbol::OneOf(residues(body, 0)?)
+    }
+    else{
+        return Err(malformed());
+    };
+    match &symbol{
+        PatternSymbol::OneOf(allowed) if allowed.is_empty() => Err(malformed()),
+        _ => Ok((symbol, repeats)),
+    }
+}
+
+impl<T> Display for Pattern<T>
+where
+    T: Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for symbol in self.symbols.iter(){
+            write!(f, "{}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> TryFrom<Vec<PatternSymbol<T>>> for Pattern<T>
+where
+    T: PartialEq + Clone
+{
+    type Error = PatternError;
+
+    fn try_from(symbols: Vec<PatternSymbol<T>>)->Result<Self, PatternError>{
+        Self::new(symbols)
+    }
+}
+
+/// A pattern made of exact blocks separated by gaps of bounded length, such as `ACGT-x(2,5)-GGC`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GappedPattern<T>{
+    blocks: Vec<Pattern<T>>,
+    gaps: Vec<(usize, usize)>,
+}
+
+impl<T> GappedPattern<T>
+where
+    T: PartialEq + Clone
+{
+    /// Creates a gapped pattern from its blocks and the (minimum, maximum) length of the gap following each block except the last.
+    pub fn new(blocks: Vec<Pattern<T>>, gaps: Vec<(usize, usize)>)->Result<Self, PatternError>{
+        if blocks.is_empty() || blocks.iter().any(|block| block.is_empty()){
+            return Err(PatternError::Empty);
+        }
+        if gaps.len()+1!=blocks.len(){
+            return Err(PatternError::MalformedElement(format!("{} blocks with {} gaps", blocks.len(), gaps.len())));
+        }
+        if let Some((min, max)) = gaps.iter().find(|(min, max)| min>max){
+            return Err(PatternError::InvalidGap(*min, *max));
+        }
+        Ok(Self { blocks, gaps })
+    }
+
+    pub fn blocks(&self)->&[Pattern<T>]{
+        &self.blocks
+    }
+
+    pub fn gaps(&self)->&[(usize, usize)]{
+        &self.gaps
+    }
+
+    /// Returns the shortest and longest span a match of the pattern can cover.
+    pub fn span_bounds(&self)->(usize, usize){
+        let blocks_len: usize = self.blocks.iter().map(|block| block.len()).sum();
+        (blocks_len+self.gaps.iter().map(|gap| gap.0).sum::<usize>(), blocks_len+self.gaps.iter().map(|gap| gap.1).sum::<usize>())
+    }
+}
+
+impl GappedPattern<char>{
+    /// Parses a nucleotide gapped pattern such as `ACGT-x(2,5)-GGC`.
+    ///
+    /// Elements are separated by `-`. Gaps are written as `x(min,max)`, `x(n)` or `x`, and every other element is an IUPAC block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::data::pattern::GappedPattern;
+    ///
+    /// let pattern = GappedPattern::from_iupac("ACGT-x(2,5)-GGC").unwrap();
+    /// assert_eq!(pattern.span_bounds(), (9, 12));
+    /// ```
+    pub fn from_iupac(s: &str)->Result<Self, PatternError>{
+        let mut elements: Vec<GappedElement> = vec![];
+        for (offset, element) in split_elements(s.trim_end()){
+            match parse_gap(element)?{
+                Some(gap) => elements.push(GappedElement::Gap(gap)),
+                None => {
+                    let offset = offset+element.chars().take_while(|c| c.is_whitespace()).count();
+                    elements.push(GappedElement::Symbols(Pattern::from_iupac_at(element.trim(), offset)?.symbols, 1));
+                },
+            }
+        }
+        Self::from_elements(elements)
+    }
+
+    /// Parses a PROSITE-like protein pattern whose `x(min,max)` elements are treated as gaps, such as `C-x(2,4)-C-x(3)-H`.
+    ///
+    /// Fixed length elements, including `x(n)`, stay inside their block.
+    pub fn from_prosite(s: &str)->Result<Self, PatternError>{
+        let mut elements: Vec<GappedElement> = vec![];
+        for (offset, element) in split_elements(s.trim_end().trim_end_matches('.')){
+            match parse_gap(element)?{
+                Some((min, max)) if min!=max => elements.push(GappedElement::Gap((min, max))),
+                _ => {
+                    let (symbol, repeats) = parse_prosite_element(element, offset)?;
+                    elements.push(GappedElement::Symbols(vec![symbol], repeats));
+                },
+            }
+        }
+        Self::from_elements(elements)
+    }
+
+    fn from_elements(elements: Vec<GappedElement>)->Result<Self, PatternError>{
+        let mut blocks: Vec<Pattern<char>> = vec![];
+        let mut gaps: Vec<(usize, usize)> = vec![];
+        let mut block: Vec<PatternSymbol<char>> = vec![];
+        let mut gap: Option<(usize, usize)> = None;
+        for element in elements{
+            match element{
+                GappedElement::Gap((min, max)) => {
+                    if block.is_empty() && blocks.is_empty(){
+                        return Err(PatternError::MalformedElement("pattern cannot start with a gap".to_string()));
+                    }
+                    if !block.is_empty(){
+                        blocks.push(Pattern::new(std::mem::take(&mut block))?);
+                    }
+                    gap = Some(gap.map_or((min, max), |(gap_min, gap_max)| (gap_min+min, gap_max+max)));
+                },
+                GappedElement::Symbols(symbols, repeats) => {
+                    if let Some(g) = gap.take(){
+                        gaps.push(g);
+                    }
+                    for _ in 0..repeats{
+                        block.extend(symbols.iter().cloned());
+                    }
+                },
+            }
+        }
+        if gap.is_some(){
+            return Err(PatternError::MalformedElement("pattern cannot end with a gap".to_string()));
+        }
+        if !block.is_empty(){
+            blocks.push(Pattern::new(block)?);
+        }
+        Self::new(blocks, gaps)
+    }
+}
+
+enum GappedElement{
+    Gap((usize, usize)),
+    Symbols(Vec<PatternSymbol<char>>, usize),
+}
+
+/// Parses elements of the form `x`, `x(n)` or `x(min,max)` into gap bounds.
+fn parse_gap(element: &str)->Result<Option<(usize, usize)>, PatternError>{
+    let element = element.trim();
+    let malformed = || PatternError::MalformedElement(element.to_string());
+    let bounds = match element.strip_prefix(['x', 'X']){
+        Some("") => return Ok(Some((1, 1))),
+        Some(bounds) => bounds,
+        None => return Ok(None),
+    };
+    let bounds = match bounds.strip_prefix('(').and_then(|x| x.strip_suffix(')')){
+        Some(bounds) => bounds,
+        None => return Err(malformed()),
+    };
+    let parsed = bounds.split(',').map(|x| x.trim().parse::<usize>().map_err(|_| malformed())).collect::<Result<Vec<usize>, PatternError>>()?;
+    match parsed.as_slice(){
+        [n] => Ok(Some((*n, *n))),
+        [min, max] if min<=max => Ok(Some((*min, *max))),
+        [min, max] => Err(PatternError::InvalidGap(*min, *max)),
+        _ => Err(malformed()),
+    }
+}
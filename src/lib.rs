@@ -30,4 +30,5 @@ pub mod suffix_node;
 pub mod suffix_tree;
 pub mod data;
 pub mod utils;
-pub mod iter;
\ No newline at end of file
+pub mod iter;
+pub mod alphabet;
\ No newline at end of file
@@ -1,4 +1,20 @@
 pub mod tree;
+pub mod frequency;
+pub mod unique;
+mod absent;
+pub mod distinguishing;
+pub mod design;
+pub mod repeats;
+pub mod strand;
+pub mod translate;
+pub mod export;
+pub mod matrix;
+pub mod similarity;
+pub mod distance;
+pub mod features;
+pub mod cluster;
+pub mod containment;
+mod annotations;
 
 use crate::data::tree_item::Character;
 use crate::suffix_tree::tree::*;
@@ -6,8 +22,11 @@ use crate::suffix_node::node::*;
 use crate::suffix_node::*;
 use crate::data::TreeItem;
 use crate::data::tree_item::TreeItem as OtherTreeItem;
+use crate::data::pattern::{Pattern, GappedPattern};
 use crate::iter::node_iter::*;
 use crate::iter::edge_iter::*;
+use crate::suffix_tree::annotations::Annotations;
+use crate::suffix_tree::translate::ReadingFrame;
 
 #[cfg(feature = "non_crypto_hash")]
 use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
@@ -15,6 +34,7 @@ use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use std::collections::{HashMap, HashSet};
 
 use std::collections::LinkedList;
+use std::borrow::Cow;
 use std::fmt::{Display, Debug};
 use std::hash::Hash;
 use std::cmp;
@@ -37,7 +57,12 @@ where
     strings: HashMap<StringID, (TreeItem<T, U>, usize)>,
     leaves: Vec<NodeID>,
     suffix_links: HashMap<NodeID, NodeID>,
-    node_data: HashMap<NodeID, HashMap<StringID, HashSet<usize>>>
+    node_data: HashMap<NodeID, HashMap<StringID, HashSet<usize>>>,
+    reverse_strands: HashSet<StringID>,
+    reverse_complements: HashMap<StringID, StringID>,
+    circular_extensions: HashMap<StringID, Vec<Character<T>>>,
+    reading_frames: HashMap<StringID, (ReadingFrame, usize)>,
+    annotations: Annotations,
 }
 
 impl<T, U> Serialize for KGST<T, U> 
@@ -49,7 +74,7 @@ where
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("KGST", 7)?;
+        let mut state = serializer.serialize_struct("KGST", 11)?;
         state.serialize_field("root", &self.root)?;
         state.serialize_field("nodes", &self.nodes)?;
         state.serialize_field("terminal_character", &self.terminal_character)?;
@@ -57,6 +82,10 @@ where
         state.serialize_field("leaves", &self.leaves)?;
         state.serialize_field("suffix_links", &self.suffix_links)?;
         state.serialize_field("node_data", &self.node_data)?;
+        state.serialize_field("reverse_strands", &self.reverse_strands)?;
+        state.serialize_field("reverse_complements", &self.reverse_complements)?;
+        state.serialize_field("circular_extensions", &self.circular_extensions)?;
+        state.serialize_field("reading_frames", &self.reading_frames)?;
         state.end()
     }
 }
@@ -91,6 +120,11 @@ where
             leaves: Vec::new(),
             suffix_links: [(0,0)].into_iter().collect(),
             node_data: [(0, [].into_iter().collect())].into_iter().collect(),
+            reverse_strands: [].into_iter().collect(),
+            reverse_complements: [].into_iter().collect(),
+            circular_extensions: [].into_iter().collect(),
+            reading_frames: [].into_iter().collect(),
+            annotations: Annotations::default(),
         }
     }
 
@@ -102,6 +136,11 @@ where
         self.leaves = Vec::new();
         self.node_data = [].into_iter().collect();
         self.suffix_links = [].into_iter().collect();
+        self.reverse_strands = [].into_iter().collect();
+        self.reverse_complements = [].into_iter().collect();
+        self.circular_extensions = [].into_iter().collect();
+        self.reading_frames = [].into_iter().collect();
+        self.annotations = Annotations::default();
     }
 
     fn leaves_of_node(&self, node_id:&NodeID, leaves:&mut Vec<NodeID>){
@@ -133,8 +172,8 @@ where
     }
 
     /// Returns the string represented by the incoming edge of the node.
-    pub fn get_node_label(&self, node_id: &NodeID)->&[Character<T>]{
-        &self.get_node_string(node_id)[*self.get_node_start(node_id)..self.get_node_start(node_id)+(self.get_node_edge_length(node_id))]
+    pub fn get_node_label(&self, node_id: &NodeID)->Cow<'_, [Character<T>]>{
+        self.get_string_range(self.get_node_string_id(node_id), *self.get_node_start(node_id), self.get_node_start(node_id)+self.get_node_edge_length(node_id))
     }
 
     fn create_node(&mut self, children: HashMap<Character<T>, usize>,
@@ -158,19 +197,37 @@ where
             }
 
     fn set_node_suffix_link(&mut self, node_id: &NodeID, suffix_link_node_id: &NodeID){
-        self.suffix_links.entry(*node_id).and_modify(|e| *e *= suffix_link_node_id).or_insert(*suffix_link_node_id);
+        self.suffix_links.insert(*node_id, *suffix_link_node_id);
+    }
+
+    /// Returns the characters in `[start, end)` of a string as indexed by the edge labels, which continue into the extension for circular strings.
+    pub(crate) fn get_string_range(&self, treeitem_id: &StringID, start: usize, end: usize)->Cow<'_, [Character<T>]>{
+        let string = self.get_treeitem_by_treeitem_id(treeitem_id).0.get_string();
+        let extension_start = string.len()-1;
+        match self.circular_extensions.get(treeitem_id){
+            Some(extension) if end>extension_start => match start>=extension_start{
+                true => Cow::Borrowed(&extension[start-extension_start..end-extension_start]),
+                false => Cow::Owned(string[start..extension_start].iter().chain(extension[..end-extension_start].iter()).cloned().collect()),
+            },
+            _ => Cow::Borrowed(&string[start..end]),
+        }
     }
 
-    fn get_string_by_treeitem_id(&self, treeitem_id: &StringID)->&[Character<T>]{
-        self.strings.get(treeitem_id).expect("TreeItem ID does not exist!").0.get_string()
+    /// Returns the character at a position of a string as indexed by the edge labels.
+    fn get_string_char(&self, treeitem_id: &StringID, idx: usize)->&Character<T>{
+        let string = self.get_treeitem_by_treeitem_id(treeitem_id).0.get_string();
+        match self.circular_extensions.get(treeitem_id){
+            Some(extension) if idx>=string.len()-1 => &extension[idx+1-string.len()],
+            _ => &string[idx],
+        }
     }
 
     fn get_node_edge_length(&self, node_id: &NodeID)->usize{
         self.get_node(node_id).get_edge_length()
     }
 
-    fn get_node_string(&self, node_id: &NodeID)->&[Character<T>]{
-        self.get_string_by_treeitem_id(self.get_node_string_id(node_id))
+    fn get_node_char(&self, node_id: &NodeID, idx: usize)->&Character<T>{
+        self.get_string_char(self.get_node_string_id(node_id), idx)
     }
 
     fn get_node_string_id(&self, node_id: &NodeID)->&usize{
@@ -181,12 +238,13 @@ where
         self.nodes.get_mut(node_id).expect("Node ID does not exist!")
     }
 
-    fn add_seq_to_leaves(&mut self, node_id: &NodeID, string_id: &StringID, start: &usize){
-        let mut leaves:Vec<NodeID> = vec![];
-        self.leaves_of_node(node_id, &mut leaves);
-        for leaf in leaves.iter(){
-            self.add_seq_to_node(leaf, string_id, start);
+    /// Stores an occurrence in a single leaf below a node, so that every occurrence is counted once by the leaves.
+    fn add_seq_to_leaf(&mut self, node_id: &NodeID, string_id: &StringID, start: &usize){
+        let mut leaf: NodeID = *node_id;
+        while let Some(child_id) = self.get_node_children(&leaf).values().min(){
+            leaf = *child_id;
         }
+        self.add_seq_to_node(&leaf, string_id, start);
     }
 
     fn get_treeitem_by_treeitem_id(&self, treeitem_id: &StringID)->&(TreeItem<T, U>, usize){
@@ -194,26 +252,18 @@ where
     }
 
     fn get_pattern_node(&self, q_string:&[T])->Option<&NodeID>{
-        let mut node_id: Option<&NodeID> = Some(&self.root);
-        // dbg!(q_string);
-        let mut c: &T = &q_string[0];
+        let mut node_id: &NodeID = &self.root;
         let mut i = 0;
-        loop {
-            node_id = self.get_node_child(node_id.unwrap(), c);
-            match node_id{
-                None => return None,
-                Some(n) => {
-                    if q_string.len()>self.get_node_depth(n){
-                        i += self.get_node_edge_length(n);
-                        c = &q_string[i];
-                        node_id = Some(n);
-                    }
-                    else{
-                        return Some(n);
-                    }
-                },
+        while i<q_string.len(){
+            node_id = self.get_node_child(node_id, &q_string[i])?;
+            for c in self.get_node_label(node_id).iter().take(q_string.len()-i){
+                if c.into_inner()!=Some(&q_string[i]){
+                    return None;
+                }
+                i += 1;
             }
         }
+        Some(node_id)
     }
 
     /// Retrieves all strings that the input slice is a suffix of.
@@ -225,42 +275,130 @@ where
 
     /// Retrieves all strings that contain the input slice as some substring.
     pub fn substring_match(&self, s:&[T]) -> HashMap<U, HashSet<usize>>{
-        let node = self.get_pattern_node(s);
-        let mut leaves:Vec<usize> = vec![];
-        let mut ids_and_indexes: HashMap<StringID, HashSet<usize>> = [].into_iter().collect();
-        match node{
-            None => {},
-            Some(i) => {
-                if self.get_node_depth(i)<s.len(){
-                    match self.get_node_parent(i){
-                        None => {},
-                        Some(_parent_id) => {self.leaves_of_node(i, &mut leaves);}
-                    }
+        match self.get_pattern_node(s){
+            None => [].into_iter().collect(),
+            Some(node_id) => self.occurrences_below(&[*node_id], s.len()),
+        }
+    }
+
+    /// Retrieves all strings that contain a substring matching the input pattern, along with the start positions of every match.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::data::pattern::Pattern;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("seq".to_string(), "GAATTCGAGTTC".chars().collect(), &0);
+    /// let matches = tree.pattern_match(&Pattern::from_iupac("GARTTC").unwrap());
+    /// assert_eq!(matches.get("seq").unwrap().len(), 2);
+    /// ```
+    pub fn pattern_match(&self, pattern: &Pattern<T>) -> HashMap<U, HashSet<usize>>{
+        self.occurrences_below(&self.get_pattern_nodes(pattern), pattern.len())
+    }
+
+    /// Returns the highest nodes whose path label starts with a match of the pattern, branching over every child allowed at each position.
+    fn get_pattern_nodes(&self, pattern: &Pattern<T>)->Vec<NodeID>{
+        let symbols = pattern.symbols();
+        let mut pattern_nodes: Vec<NodeID> = vec![];
+        let mut stack: Vec<(NodeID, usize)> = vec![(self.root, 0)];
+        while let Some((node_id, matched)) = stack.pop(){
+            if matched==symbols.len(){
+                pattern_nodes.push(node_id);
+                continue;
+            }
+            for child_node_id in self.get_node_children(&node_id).values(){
+                let mut i = matched;
+                let edge_matches = self.get_node_label(child_node_id).iter().take(symbols.len()-matched).all(|c| {
+                    let symbol_matches = c.into_inner().is_some_and(|x| x!=&self.terminal_character && symbols[i].matches(x));
+                    i += 1;
+                    symbol_matches
+                });
+                if edge_matches{
+                    stack.push((*child_node_id, i));
                 }
-                else{
-                    self.leaves_of_node(i, &mut leaves);
+            }
+        }
+        pattern_nodes
+    }
+
+    /// Retrieves the spans of every match of a gapped pattern as half-open `(start, end)` intervals, sorted by start position.
+    /// 
+    /// Each block is located with the tree and the occurrences are then joined per string under the gap constraints, so every block must fit within the truncation depth of a string while the full span does not have to.
+    /// The join takes time proportional to the number of block occurrences plus the number of compatible occurrence pairs.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::data::pattern::GappedPattern;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("seq".to_string(), "ACGTAAGGCTTACGTAAAAAAGGC".chars().collect(), &0);
+    /// let matches = tree.gapped_match(&GappedPattern::from_iupac("ACGT-x(2,5)-GGC").unwrap());
+    /// assert_eq!(matches.get("seq").unwrap(), &vec![(0, 9)]);
+    /// ```
+    pub fn gapped_match(&self, pattern: &GappedPattern<T>) -> HashMap<U, Vec<(usize, usize)>>{
+        let block_occurrences: Vec<HashMap<StringID, HashSet<usize>>> = pattern.blocks().iter()
+            .map(|block| self.string_occurrences_below(&self.get_pattern_nodes(block), block.len()))
+            .collect();
+        let mut spans: HashMap<U, Vec<(usize, usize)>> = [].into_iter().collect();
+        for (string_id, first_starts) in block_occurrences[0].iter(){
+            // Every reachable block start along with the starts of the first block that lead to it.
+            let mut reachable: Vec<(usize, Vec<usize>)> = first_starts.iter().sorted().map(|start| (*start, vec![*start])).collect();
+            for (block_idx, (min_gap, max_gap)) in pattern.gaps().iter().enumerate(){
+                let prev_len = pattern.blocks()[block_idx].len();
+                let next_starts: Vec<usize> = match block_occurrences[block_idx+1].get(string_id){
+                    None => vec![],
+                    Some(starts) => starts.iter().copied().sorted().collect(),
+                };
+                let mut next_reachable: Vec<Vec<usize>> = vec![vec![]; next_starts.len()];
+                for (prev_start, match_starts) in reachable.iter(){
+                    let lower = next_starts.partition_point(|x| *x<prev_start+prev_len+min_gap);
+                    let upper = next_starts.partition_point(|x| *x<=prev_start+prev_len+max_gap);
+                    for reached in next_reachable[lower..upper].iter_mut(){
+                        reached.extend(match_starts.iter());
+                    }
                 }
+                reachable = next_starts.into_iter().zip(next_reachable)
+                    .filter(|(_, match_starts)| !match_starts.is_empty())
+                    .map(|(start, match_starts)| (start, match_starts.into_iter().sorted().dedup().collect()))
+                    .collect();
+            }
+            let last_len = pattern.blocks()[pattern.blocks().len()-1].len();
+            let string_spans: Vec<(usize, usize)> = reachable.into_iter()
+                .flat_map(|(last_start, match_starts)| match_starts.into_iter().map(move |start| (start, last_start+last_len)))
+                .sorted()
+                .collect();
+            if !string_spans.is_empty(){
+                spans.entry(self.get_treeitem_by_treeitem_id(string_id).0.get_id().clone()).or_default().extend(string_spans);
             }
         }
+        spans
+    }
+
+    /// Collects the occurrences stored in the leaves below each input node, skipping strings that were truncated below the query length.
+    fn occurrences_below(&self, node_ids: &[NodeID], query_len: usize) -> HashMap<U, HashSet<usize>>{
+        self.string_occurrences_below(node_ids, query_len).into_iter()
+            .map(|(k, v)| (self.get_treeitem_by_treeitem_id(&k).0.get_id().clone(), v))
+            .collect::<HashMap<U, HashSet<usize>>>()
+    }
+
+    fn string_occurrences_below(&self, node_ids: &[NodeID], query_len: usize) -> HashMap<StringID, HashSet<usize>>{
+        let mut leaves:Vec<usize> = vec![];
+        for node_id in node_ids.iter(){
+            self.leaves_of_node(node_id, &mut leaves);
+        }
+        let mut ids_and_indexes: HashMap<StringID, HashSet<usize>> = [].into_iter().collect();
         for leaf in leaves{
             for (treeitem_id, idx) in self.get_node_data(&leaf){
-                match ids_and_indexes.get_mut(treeitem_id){
-                    None => {
-                        if self.get_treeitem_by_treeitem_id(treeitem_id).1>=s.len(){
-                            ids_and_indexes.insert(*treeitem_id, idx.clone());
-                        }
-                    },
-                    Some(idxs) => {
-                        if self.get_treeitem_by_treeitem_id(treeitem_id).1>=s.len(){
-                            for i in idx.iter(){
-                                idxs.insert(*i);
-                            }
-                        }
-                    },
+                if self.get_treeitem_by_treeitem_id(treeitem_id).1>=query_len{
+                    ids_and_indexes.entry(*treeitem_id).or_default().extend(idx.iter().cloned());
                 }
             }
         }
-        ids_and_indexes.into_iter().map(|(k, v)| (self.strings.get(&k).cloned().unwrap().0.get_id().clone(), v)).collect::<HashMap<U, HashSet<usize>>>()
+        ids_and_indexes
     }
 
     fn get_node_children(&self, node_id: &NodeID)-> &HashMap<Character<T>, usize>{
@@ -324,9 +462,11 @@ where
     }
 
     /// inserts all suffixes of a string into the tree. If max_depth>0, all substrings of length==max_depth are inserted. 
+    /// Strings can be inserted with different depths, and each one is only reported for queries up to its own depth.
     pub fn insert(&mut self, k: U, v: Vec<T>, max_depth: &usize){
         let seq_id: U = k.clone();
         let mut seq: Vec<T> = v.clone();
+        self.annotations = Annotations::default();
 
         seq.push(self.terminal_character.clone());
 
@@ -352,6 +492,7 @@ where
                 let next_node = self.get_node(&active_node).get_child(&active_edge).cloned();
                 match next_node{
                     None => {
+                        let active_node_is_leaf = self.is_leaf(&active_node);
                         let new_leaf_node_id: usize = self.create_node(
                             [].into_iter().collect(),
                             Some(new_string_id),
@@ -362,8 +503,11 @@ where
                         );
                         self.set_node_child_id(&active_edge, &active_node, &new_leaf_node_id);
                         self.add_suffix_link(&active_node, &mut need_suffix_link);
-                        let active_node_data = self.get_node_data(&active_node).clone();
-                        self.add_data_to_node(&new_leaf_node_id, active_node_data);
+                        if active_node_is_leaf{
+                            // A truncated leaf is being extended, so its occurrences move down to the new leaf.
+                            let active_node_data = std::mem::take(self.node_data.get_mut(&active_node).expect("Node ID does not exist!"));
+                            self.add_data_to_node(&new_leaf_node_id, active_node_data);
+                        }
                         start_idx += 1;
                     },
                     Some(next_node_id) => {
@@ -372,9 +516,10 @@ where
                             active_node = next_node_id;
                             continue;
                         }
-                        else if self.get_node_string(&next_node_id)[self.get_node_start(&next_node_id) + curr_pos-start_idx-self.get_node_depth(&active_node)] == Character::Char(seq[curr_pos].clone()){   
-                            self.add_seq_to_leaves(&next_node_id, &new_string_id, &start_idx);
-                            if curr_pos==seq.len()-1{
+                        else if self.get_node_char(&next_node_id, self.get_node_start(&next_node_id) + curr_pos-start_idx-self.get_node_depth(&active_node)) == &Character::Char(seq[curr_pos].clone()){   
+                            // The suffix is complete once it reaches the end of the string or the truncation depth, even if the path continues below for deeper strings.
+                            if curr_pos==seq.len()-1 || curr_pos+1-start_idx==max_depth{
+                                self.add_seq_to_leaf(&next_node_id, &new_string_id, &start_idx);
                                 start_idx+=1;
                             }
                             else{
@@ -385,7 +530,7 @@ where
                         else{
                             let split_node_id: usize = self.create_node(
                                 [
-                                            (self.get_node_string(&next_node_id)[self.get_node_start(&next_node_id) + curr_pos-start_idx-self.get_node_depth(&active_node)].clone(), next_node_id)
+                                            (self.get_node_char(&next_node_id, self.get_node_start(&next_node_id) + curr_pos-start_idx-self.get_node_depth(&active_node)).clone(), next_node_id)
                                             ].into_iter().collect(),
                                             Some(*self.get_node_string_id(&next_node_id)),
                                             [(new_string_id, [start_idx].into_iter().collect())].into_iter().collect(),
@@ -421,6 +566,56 @@ where
         
     }
 
+    /// Inserts a circular string, such as a plasmid or a mitochondrial genome, so that the prefixes of every rotation up to max_depth (or the length of the string if max_depth==0) are searchable.
+    /// 
+    /// The string is extended with its own prefix before insertion and occurrences starting in the extension are dropped,
+    /// so positions are reported modulo the length of the string and matches wrapping around the origin are found once.
+    /// The stored string keeps the original sequence and length, along with the part of the extension the edge labels read, and queries longer than the string do not match.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert_circular("plasmid".to_string(), "TACGGA".chars().collect(), &0);
+    /// assert_eq!(tree.substring_match(&['G', 'A', 'T', 'A']).get("plasmid").unwrap(), &[4].into_iter().collect());
+    /// assert_eq!(tree.count_occurrences(&['A']), 2);
+    /// ```
+    pub fn insert_circular(&mut self, k: U, v: Vec<T>, max_depth: &usize){
+        let string_len = v.len();
+        let wrap_len = match max_depth {
+            &0 => string_len,
+            _ => cmp::min(*max_depth, string_len),
+        };
+        let mut seq: Vec<T> = v.clone();
+        seq.extend_from_within(..wrap_len.saturating_sub(1));
+        let string_id: StringID = self.strings.len();
+        let first_new_node: NodeID = self.nodes.len();
+        self.insert(k, seq, &wrap_len);
+
+        let (extended, max_depth) = self.strings.remove(&string_id).expect("TreeItem ID does not exist!");
+        let mut original: Vec<T> = v;
+        original.push(self.terminal_character.clone());
+        let mut item: TreeItem<T, U> = TreeItem::new(extended.get_id().clone(), original);
+        for node_id in extended.get_nodes(){
+            item.add_data_to_node(node_id);
+        }
+        self.strings.insert(string_id, (item, max_depth));
+        self.circular_extensions.insert(string_id, extended.get_string()[string_len..].to_vec());
+
+        // Occurrences of the new string are only stored in the nodes it created or added its data to.
+        for node_id in extended.get_nodes().copied().chain(first_new_node..self.nodes.len()){
+            let node_data = self.node_data.get_mut(&node_id).expect("Node ID does not exist!");
+            if let Some(starts) = node_data.get_mut(&string_id){
+                starts.retain(|start| *start<string_len);
+                if starts.is_empty(){
+                    node_data.remove(&string_id);
+                }
+            }
+        }
+    }
+
     //Checks if a string with string_id already exists in tree.
     pub fn contains(&self, string_id: &U)->bool{
         let string_ids: HashSet<&U> = self.strings.values().map(|x| x.0.get_id()).collect();
@@ -495,8 +690,8 @@ where
     fn get_node_label(&self, node_id: &NodeID)->Vec<T>{
         let node_edge_length  = self.get_node_edge_length(node_id);
         let node_start = *self.get_node_start(node_id);
-        let string  = self.get_string_by_treeitem_id(self.get_node_string_id(node_id))[node_start..node_start+node_edge_length].iter();
-        string.map(|x| x.into_inner().cloned().expect("Terminal Character cannot be unwrapped!")).collect_vec()
+        let string = self.get_string_range(self.get_node_string_id(node_id), node_start, node_start+node_edge_length);
+        string.iter().map(|x| x.into_inner().cloned().expect("Terminal Character cannot be unwrapped!")).collect_vec()
     }
     fn get_node_path_label(&self, _node_id: &NodeID)->&[T]{
         todo!();
@@ -0,0 +1,115 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::{Character, TreeItem as OtherTreeItem};
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::{HashMap, HashSet};
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::cmp::Ordering;
+use serde::Serialize;
+
+/// Whether a subtree has any occurrence, and the characters preceding its occurrences.
+type LeftContext<T> = (bool, HashSet<T>);
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the minimal absent words of the whole collection of length at most `max_len`, sorted by length and then lexicographically.
+    /// 
+    /// A word `aub` is a minimal absent word if it does not occur in any string while `au` and `ub` both do. The middle `u` of such a word is always a branching node,
+    /// so the words are read from the children of every node and the characters preceding the occurrences below each child. Words longer than the smallest depth of a truncated string are not reported.
+    /// 
+    /// Suffix links are not followed: the preceding characters are merged bottom-up instead, which takes O(nσ) time for n nodes and σ distinct symbols, plus the total length of the reported words.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abaab".chars().collect(), &0);
+    /// assert_eq!(tree.minimal_absent_words(3), vec![vec!['b', 'b'], vec!['a', 'a', 'a'], vec!['b', 'a', 'b']]);
+    /// ```
+    pub fn minimal_absent_words(&self, max_len: usize)->Vec<Vec<T>>{
+        self.absent_words(max_len, None)
+    }
+
+    /// Returns the minimal absent words of every string on its own, over the alphabet of the whole collection, with length at most `max_len`.
+    /// 
+    /// Words of each string are sorted by length and then lexicographically, and words longer than the depth of a truncated string are not reported for it.
+    /// Every string takes a traversal of the whole tree, so this is O(mnσ) for m strings.
+    pub fn string_minimal_absent_words(&self, max_len: usize)->HashMap<U, Vec<Vec<T>>>{
+        self.get_strings().iter()
+            .map(|(string_id, (item, _))| (item.get_id().clone(), self.absent_words(max_len, Some(string_id))))
+            .collect()
+    }
+
+    /// Enumerates minimal absent words bottom-up, restricted to the occurrences of a single string if one is given.
+    fn absent_words(&self, max_len: usize, string_filter: Option<&StringID>)->Vec<Vec<T>>{
+        let string_depths = self.string_depths();
+        // Only strings truncated before their end can hide longer words, and only the filtered string matters for its own words.
+        let truncation_depth = match string_filter{
+            None => self.truncation_depth(),
+            Some(string_id) => {
+                let (item, max_depth) = &self.get_strings()[string_id];
+                (*max_depth<item.get_string().len()).then_some(*max_depth)
+            },
+        };
+        let max_len = truncation_depth.map_or(max_len, |depth| depth.min(max_len));
+        let mut absent_words: Vec<Vec<T>> = vec![];
+
+        let mut left_chars: HashMap<NodeID, LeftContext<T>> = [].into_iter().collect();
+        for node_id in self.iter_nodes_post(){
+            let node = self.get_node(&node_id);
+            let mut present = false;
+            let mut node_left_chars: HashSet<T> = [].into_iter().collect();
+            if node.is_leaf(){
+                for (string_id, starts) in self.get_node_data(&node_id){
+                    if string_filter.is_some_and(|x| x!=string_id){
+                        continue;
+                    }
+                    present = true;
+                    let string = self.get_strings()[string_id].0.get_string();
+                    node_left_chars.extend(starts.iter().filter(|start| **start>0).filter_map(|start| string[start-1].into_inner().cloned()));
+                }
+            }
+            let children: Vec<(&Character<T>, LeftContext<T>)> = node.get_children().iter()
+                .map(|(edge, child_id)| (edge, left_chars.remove(child_id).expect("Child visited before parent")))
+                .collect();
+            for (_, (child_present, child_left_chars)) in children.iter(){
+                present |= child_present;
+                node_left_chars.extend(child_left_chars.iter().cloned());
+            }
+            if string_depths[node_id]+2<=max_len && string_depths[node_id]==self.node_text_depth(&node_id){
+                let middle: Vec<T> = self.node_path_label(&node_id).iter().filter_map(|c| c.into_inner().cloned()).collect();
+                for (edge, (child_present, child_left_chars)) in children.iter(){
+                    let last = match edge.into_inner(){
+                        Some(c) if *child_present && c!=&self.terminal_character => c,
+                        _ => continue,
+                    };
+                    for first in node_left_chars.difference(child_left_chars){
+                        let mut word: Vec<T> = vec![first.clone()];
+                        word.extend(middle.iter().cloned());
+                        word.push(last.clone());
+                        absent_words.push(word);
+                    }
+                }
+            }
+            left_chars.insert(node_id, (present, node_left_chars));
+        }
+
+        if let Some(string_id) = string_filter.filter(|_| max_len>=1){
+            let string_chars: HashSet<&Character<T>> = self.get_strings()[string_id].0.get_string().iter().collect();
+            let alphabet: HashSet<&Character<T>> = self.get_strings().values().flat_map(|(item, _)| item.get_string().iter()).collect();
+            absent_words.extend(alphabet.difference(&string_chars).filter_map(|c| c.into_inner().cloned()).map(|c| vec![c]));
+        }
+        absent_words.sort_by(|a, b| a.len().cmp(&b.len()).then(a.partial_cmp(b).unwrap_or(Ordering::Equal)));
+        absent_words
+    }
+}
@@ -0,0 +1,385 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::Character;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::{HashMap, HashSet};
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::sync::OnceLock;
+use std::borrow::Cow;
+use serde::Serialize;
+use itertools::Itertools;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+
+/// Structures derived from a finished tree. Each one is built on first use and discarded whenever the tree is modified.
+#[derive(Debug, Default)]
+pub(crate) struct Annotations{
+    pub(crate) string_depths: OnceLock<Vec<usize>>,
+    pub(crate) node_counts: OnceLock<NodeCounts>,
+    pub(crate) document_listing: OnceLock<DocumentListing>,
+    pub(crate) suffix_leaves: OnceLock<HashMap<StringID, Vec<NodeID>>>,
+    pub(crate) lca_index: OnceLock<LcaIndex>,
+}
+
+/// Number of occurrences and of distinct ids in the subtree of every node, indexed by node id.
+///
+/// Strands and reading frames inserted under the same id count as one id. The counts include every string below a node, so they only hold for queries no longer than the smallest truncation depth.
+#[derive(Debug)]
+pub(crate) struct NodeCounts{
+    pub(crate) occurrences: Vec<usize>,
+    pub(crate) strings: Vec<usize>,
+    pub(crate) max_query_len: usize,
+}
+
+/// Sparse table answering range minimum queries in constant time after O(n log n) preprocessing.
+#[derive(Debug)]
+pub(crate) struct SparseTable{
+    values: Vec<usize>,
+    table: Vec<Vec<usize>>,
+}
+
+impl SparseTable{
+    pub(crate) fn new(values: Vec<usize>)->Self{
+        let mut table: Vec<Vec<usize>> = vec![(0..values.len()).collect()];
+        let mut width = 1;
+        while 2*width<=values.len(){
+            let prev = table.last().unwrap();
+            let level = (0..=values.len()-2*width).map(|i| {
+                let (left, right) = (prev[i], prev[i+width]);
+                if values[right]<values[left] {right} else {left}
+            }).collect();
+            table.push(level);
+            width *= 2;
+        }
+        Self { values, table }
+    }
+
+    /// Returns the index of the smallest value in the half-open range `[start, end)`, preferring the leftmost one on ties.
+    pub(crate) fn argmin(&self, start: usize, end: usize)->usize{
+        let level = (end-start).ilog2() as usize;
+        let (left, right) = (self.table[level][start], self.table[level][end-(1<<level)]);
+        if self.values[right]<self.values[left] {right} else {left}
+    }
+
+    pub(crate) fn value(&self, idx: usize)->usize{
+        self.values[idx]
+    }
+}
+
+/// The strings of the leaves listed in depth first order, with the range of that listing covered by every node.
+///
+/// Each entry also stores one more than the index of the previous entry of the same string (0 if there is none),
+/// so that the distinct strings of a range `[start, end)` are exactly its entries whose value is at most `start`.
+/// The entries of every string are kept with their running number of occurrences, so the occurrences of a string in a range are found by binary search.
+#[derive(Debug)]
+pub(crate) struct DocumentListing{
+    documents: Vec<StringID>,
+    previous: SparseTable,
+    ranges: Vec<(usize, usize)>,
+    string_entries: HashMap<StringID, Vec<(usize, usize)>>,
+}
+
+impl DocumentListing{
+    /// Returns the position of a leaf in the depth first order of the tree.
+    pub(crate) fn leaf_rank(&self, leaf: &NodeID)->usize{
+        self.ranges[*leaf].0
+    }
+
+    /// Returns the distinct strings in the subtree of a node in time proportional to their number.
+    pub(crate) fn list(&self, node_id: &NodeID)->Vec<StringID>{
+        let mut documents: Vec<StringID> = vec![];
+        let (node_start, _) = self.ranges[*node_id];
+        let mut stack: Vec<(usize, usize)> = vec![self.ranges[*node_id]];
+        while let Some((start, end)) = stack.pop(){
+            if start>=end{
+                continue;
+            }
+            let idx = self.previous.argmin(start, end);
+            if self.previous.value(idx)>node_start{
+                continue;
+            }
+            documents.push(self.documents[idx]);
+            stack.push((start, idx));
+            stack.push((idx+1, end));
+        }
+        documents
+    }
+
+    /// Returns the number of occurrences of a string in the subtree of a node in logarithmic time.
+    pub(crate) fn occurrences(&self, node_id: &NodeID, string_id: &StringID)->usize{
+        let (start, end) = self.ranges[*node_id];
+        let entries = &self.string_entries[string_id];
+        let running = |position: usize| match entries.partition_point(|(idx, _)| *idx<position){
+            0 => 0,
+            num_entries => entries[num_entries-1].1,
+        };
+        running(end)-running(start)
+    }
+}
+
+/// Answers lowest common ancestor queries with range minimum queries over the string depths of an Euler tour.
+#[derive(Debug)]
+pub(crate) struct LcaIndex{
+    tour: Vec<NodeID>,
+    first_visit: Vec<usize>,
+    depths: SparseTable,
+}
+
+impl LcaIndex{
+    pub(crate) fn lca(&self, node_1: &NodeID, node_2: &NodeID)->NodeID{
+        let (first_1, first_2) = (self.first_visit[*node_1], self.first_visit[*node_2]);
+        self.tour[self.depths.argmin(first_1.min(first_2), first_1.max(first_2)+1)]
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the length of the path label of every node, indexed by node id.
+    pub(crate) fn string_depths(&self)->&[usize]{
+        self.annotations.string_depths.get_or_init(|| {
+            let mut string_depths: Vec<usize> = vec![0; self.num_nodes()];
+            for node_id in self.iter_nodes_pre(){
+                for child_id in self.get_node(&node_id).get_children().values(){
+                    string_depths[*child_id] = string_depths[node_id]+self.get_node(child_id).get_edge_length();
+                }
+            }
+            string_depths
+        })
+    }
+
+    /// Returns the path label of a node as a range of the string that created it.
+    pub(crate) fn node_path_label(&self, node_id: &NodeID)->Cow<'_, [Character<T>]>{
+        if node_id==&self.root{
+            return Cow::Borrowed(&[]);
+        }
+        let node = self.get_node(node_id);
+        let start = node.get_start()+node.get_edge_length()-self.string_depths()[*node_id];
+        self.get_string_range(node.get_string_id().expect("Node ID is root node"), start, node.get_start()+node.get_edge_length())
+    }
+
+    /// Returns the length of the longest prefix of the path label of a node that does not contain the terminal character.
+    pub(crate) fn node_text_depth(&self, node_id: &NodeID)->usize{
+        let path_label = self.node_path_label(node_id);
+        match path_label.last().and_then(|c| c.into_inner()){
+            Some(c) if c==&self.terminal_character => path_label.len()-1,
+            _ => path_label.len(),
+        }
+    }
+
+    /// Returns the smallest depth of the strings that were truncated before their end, if any.
+    pub(crate) fn truncation_depth(&self)->Option<usize>{
+        self.get_strings().values()
+            .filter(|(item, max_depth)| *max_depth<item.get_string().len())
+            .map(|(_, max_depth)| *max_depth)
+            .min()
+    }
+
+    pub(crate) fn node_counts(&self)->&NodeCounts{
+        self.annotations.node_counts.get_or_init(|| self.build_node_counts())
+    }
+
+    /// Counts occurrences and distinct ids bottom-up, merging the id sets of the children into the largest one.
+    fn build_node_counts(&self)->NodeCounts{
+        let string_rows = self.string_rows();
+        let mut occurrences: Vec<usize> = vec![0; self.num_nodes()];
+        let mut strings: Vec<usize> = vec![0; self.num_nodes()];
+        let mut string_sets: HashMap<NodeID, HashSet<usize>> = [].into_iter().collect();
+        for node_id in self.iter_nodes_post(){
+            let node = self.get_node(&node_id);
+            let node_strings: HashSet<usize> = match node.is_leaf(){
+                true => {
+                    occurrences[node_id] = self.get_node_data(&node_id).values().map(|starts| starts.len()).sum();
+                    self.get_node_data(&node_id).keys().map(|string_id| string_rows[string_id]).collect()
+                },
+                false => {
+                    let mut child_sets: Vec<HashSet<usize>> = node.get_children().values()
+                        .map(|child_id| string_sets.remove(child_id).unwrap_or_default())
+                        .collect();
+                    occurrences[node_id] = node.get_children().values().map(|child_id| occurrences[*child_id]).sum();
+                    child_sets.sort_by_key(|set| std::cmp::Reverse(set.len()));
+                    let mut merged = child_sets.swap_remove(0);
+                    for child_set in child_sets{
+                        merged.extend(child_set);
+                    }
+                    merged
+                },
+            };
+            strings[node_id] = node_strings.len();
+            string_sets.insert(node_id, node_strings);
+        }
+        NodeCounts { occurrences, strings, max_query_len: self.truncation_depth().unwrap_or(usize::MAX) }
+    }
+
+    /// Returns the number of occurrences and of distinct ids of the prefix of length `len` of the path label of a node.
+    ///
+    /// Strings truncated below `len` are not counted, so prefixes longer than the smallest truncation depth are counted from the leaves below the node.
+    pub(crate) fn substring_counts(&self, node_id: &NodeID, len: usize)->(usize, usize){
+        let node_counts = self.node_counts();
+        match len<=node_counts.max_query_len{
+            true => (node_counts.occurrences[*node_id], node_counts.strings[*node_id]),
+            false => {
+                let occurrences = self.string_occurrences_below(&[*node_id], len);
+                let strings = occurrences.keys().map(|string_id| self.get_strings()[string_id].0.get_id()).unique().count();
+                (occurrences.values().map(|starts| starts.len()).sum(), strings)
+            },
+        }
+    }
+
+    pub(crate) fn document_listing(&self)->&DocumentListing{
+        self.annotations.document_listing.get_or_init(|| self.build_document_listing())
+    }
+
+    fn build_document_listing(&self)->DocumentListing{
+        let mut documents: Vec<StringID> = vec![];
+        let mut string_entries: HashMap<StringID, Vec<(usize, usize)>> = [].into_iter().collect();
+        let mut ranges: Vec<(usize, usize)> = vec![(0, 0); self.num_nodes()];
+        let mut stack: Vec<(NodeID, bool)> = vec![(self.root, false)];
+        while let Some((node_id, visited)) = stack.pop(){
+            if visited{
+                ranges[node_id].1 = documents.len();
+                continue;
+            }
+            ranges[node_id].0 = documents.len();
+            let node = self.get_node(&node_id);
+            if node.is_leaf(){
+                for (string_id, starts) in self.get_node_data(&node_id).iter().sorted_by_key(|(string_id, _)| **string_id){
+                    let entries = string_entries.entry(*string_id).or_default();
+                    let running = entries.last().map_or(0, |(_, running)| *running);
+                    entries.push((documents.len(), running+starts.len()));
+                    documents.push(*string_id);
+                }
+            }
+            stack.push((node_id, true));
+            for child_id in node.get_children().values().sorted().rev(){
+                stack.push((*child_id, false));
+            }
+        }
+        let mut last_seen: HashMap<StringID, usize> = [].into_iter().collect();
+        let previous: Vec<usize> = documents.iter().enumerate()
+            .map(|(idx, string_id)| last_seen.insert(*string_id, idx+1).unwrap_or(0))
+            .collect();
+        DocumentListing { documents, previous: SparseTable::new(previous), ranges, string_entries }
+    }
+
+    /// Returns the distinct strings below a node, skipping strings that were truncated below `len`.
+    pub(crate) fn listed_strings(&self, node_id: &NodeID, len: usize)->Vec<StringID>{
+        self.document_listing().list(node_id).into_iter()
+            .filter(|string_id| self.get_strings()[string_id].1>=len)
+            .collect()
+    }
+
+    /// Returns, for every string, the leaf containing the suffix starting at each position of the string.
+    pub(crate) fn suffix_leaves(&self)->&HashMap<StringID, Vec<NodeID>>{
+        self.annotations.suffix_leaves.get_or_init(|| {
+            let mut suffix_leaves: HashMap<StringID, Vec<NodeID>> = self.get_strings().iter()
+                .map(|(string_id, (item, _))| (*string_id, vec![self.root; item.get_string().len()]))
+                .collect();
+            for node_id in 0..self.num_nodes(){
+                if node_id==self.root || !self.get_node(&node_id).is_leaf(){
+                    continue;
+                }
+                for (string_id, starts) in self.get_node_data(&node_id){
+                    let leaves = suffix_leaves.get_mut(string_id).expect("TreeItem ID does not exist!");
+                    for start in starts{
+                        leaves[*start] = node_id;
+                    }
+                }
+            }
+            suffix_leaves
+        })
+    }
+
+    pub(crate) fn lca_index(&self)->&LcaIndex{
+        self.annotations.lca_index.get_or_init(|| {
+            let string_depths = self.string_depths();
+            let mut tour: Vec<NodeID> = vec![];
+            let mut first_visit: Vec<usize> = vec![0; self.num_nodes()];
+            let mut stack: Vec<(NodeID, bool)> = vec![(self.root, false)];
+            while let Some((node_id, visited)) = stack.pop(){
+                if !visited{
+                    first_visit[node_id] = tour.len();
+                    for child_id in self.get_node(&node_id).get_children().values().sorted().rev(){
+                        stack.push((node_id, true));
+                        stack.push((*child_id, false));
+                    }
+                }
+                tour.push(node_id);
+            }
+            let depths = SparseTable::new(tour.iter().map(|node_id| string_depths[*node_id]).collect());
+            LcaIndex { tour, first_visit, depths }
+        })
+    }
+
+    /// Returns the length of the longest common prefix of two suffixes, as far as it is represented in the tree.
+    pub(crate) fn suffix_lcp(&self, string_1: &StringID, start_1: usize, string_2: &StringID, start_2: usize)->usize{
+        let suffix_leaves = self.suffix_leaves();
+        let (leaf_1, leaf_2) = (suffix_leaves[string_1][start_1], suffix_leaves[string_2][start_2]);
+        let lca = self.lca_index().lca(&leaf_1, &leaf_2);
+        self.node_text_depth(&lca)
+    }
+
+    /// Returns the number of times the input slice occurs in the strings of the tree.
+    /// 
+    /// Runs in O(|s|) time using per-node counts that are computed once after the last insertion.
+    /// Strings truncated below |s| are not counted, and when strings were inserted with different depths, queries longer than the smallest one are counted from the leaves below the match.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcabxabcd".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "xabcx".chars().collect(), &0);
+    /// assert_eq!(tree.count_occurrences(&['a', 'b', 'c']), 3);
+    /// assert_eq!(tree.count_strings(&['a', 'b', 'c']), 2);
+    /// ```
+    pub fn count_occurrences(&self, s:&[T])->usize{
+        match self.get_pattern_node(s){
+            None => 0,
+            Some(node_id) => self.substring_counts(node_id, s.len()).0,
+        }
+    }
+
+    /// Returns the number of distinct ids in the tree whose strings contain the input slice, counting strands and reading frames inserted under the same id once.
+    /// 
+    /// Runs in O(|s|) time using per-node counts that are computed once after the last insertion, with the same handling of truncated strings as [`KGST::count_occurrences`].
+    pub fn count_strings(&self, s:&[T])->usize{
+        match self.get_pattern_node(s){
+            None => 0,
+            Some(node_id) => self.substring_counts(node_id, s.len()).1,
+        }
+    }
+
+    /// Retrieves the ids of all strings that contain the input slice, without enumerating its occurrences.
+    /// 
+    /// Runs in O(|s| + d) time, where d is the number of strings below the match, using a document listing structure built once after the last insertion.
+    /// Strings truncated below |s| are left out, as in [`KGST::substring_match`].
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcabxabcd".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "xabcx".chars().collect(), &0);
+    /// let strings = tree.strings_containing(&['a', 'b', 'x']);
+    /// assert!(strings.contains("first") && !strings.contains("second"));
+    /// ```
+    pub fn strings_containing(&self, s:&[T])->HashSet<U>{
+        match self.get_pattern_node(s){
+            None => [].into_iter().collect(),
+            Some(node_id) => self.listed_strings(node_id, s.len()).into_iter()
+                .map(|string_id| self.get_strings()[&string_id].0.get_id().clone())
+                .collect(),
+        }
+    }
+}
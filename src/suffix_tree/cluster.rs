@@ -0,0 +1,159 @@
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use super::KGST;
+use super::matrix::NodeWeight;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Criteria for linking two strings during clustering.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ClusterLink{
+    /// The strings share a substring of at least this length.
+    SharedSubstring(usize),
+    /// The strings share at least `min_fraction` of the distinct k-mers of the one with fewer k-mers.
+    SharedKmers{ k: usize, min_fraction: f64 },
+}
+
+/// Clustering methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ClusterMethod{
+    /// Clusters are the connected components of the links.
+    SingleLinkage,
+    /// Strings are visited from longest to shortest and join the linked centroid sharing the most k-mers with them, or become a new centroid.
+    GreedyCentroid,
+}
+
+/// A cluster of strings along with its representative, the longest member or the centroid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cluster<U>{
+    pub representative: U,
+    pub members: Vec<U>,
+}
+
+/// Returns the representative of the set containing `row`, compressing the path to it.
+fn find(parents: &mut [usize], row: usize)->usize{
+    let mut root = row;
+    while parents[root]!=root{
+        root = parents[root];
+    }
+    let mut row = row;
+    while parents[row]!=root{
+        (parents[row], row) = (root, parents[row]);
+    }
+    root
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Clusters the ids of the tree by the substrings they share.
+    ///
+    /// Two ids share a substring of length `k` exactly when both have a suffix below the locus of the substring at depth `k`, so only ids below a common locus are ever compared.
+    /// Clusters are ordered by their representatives from longest to shortest id, with ties in insertion order, and list their members in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::cluster::{ClusterLink, ClusterMethod};
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcdef".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "xxcdeyy".chars().collect(), &0);
+    /// tree.insert("third".to_string(), "yyzz".chars().collect(), &0);
+    /// let clusters = tree.cluster(ClusterLink::SharedSubstring(2), ClusterMethod::SingleLinkage);
+    /// assert_eq!(clusters.len(), 1);
+    /// let clusters = tree.cluster(ClusterLink::SharedSubstring(3), ClusterMethod::SingleLinkage);
+    /// assert_eq!(clusters[0].representative, "second".to_string());
+    /// assert_eq!(clusters[0].members, vec!["first".to_string(), "second".to_string()]);
+    /// ```
+    pub fn cluster(&self, link: ClusterLink, method: ClusterMethod)->Vec<Cluster<U>>{
+        let (k, min_fraction) = match link{
+            ClusterLink::SharedSubstring(min_len) => (min_len, 0.0),
+            ClusterLink::SharedKmers{ k, min_fraction } => (k, min_fraction),
+        };
+        let item_strings = self.item_string_ids();
+        let lengths: Vec<usize> = item_strings.iter()
+            .map(|(_, string_ids)| string_ids.iter().map(|string_id| self.get_strings()[string_id].0.get_string().len()-1).sum())
+            .collect();
+        let columns = self.incidence_matrix(NodeWeight::Binary).columns();
+        let loci: Vec<&Vec<(usize, usize)>> = self.kmer_loci(k).0.into_iter().map(|(_, node_id)| &columns[node_id]).collect();
+        let mut row_loci: Vec<Vec<usize>> = vec![vec![]; item_strings.len()];
+        for (locus, rows) in loci.iter().enumerate(){
+            for (row, _) in rows.iter(){
+                row_loci[*row].push(locus);
+            }
+        }
+        // Counts the k-mers shared with every accepted row, keeping the ones that are linked.
+        let linked_rows = |row: usize, accepted: &dyn Fn(usize)->bool|{
+            let mut shared: HashMap<usize, usize> = [].into_iter().collect();
+            for locus in row_loci[row].iter(){
+                for (other_row, _) in loci[*locus].iter().filter(|(other_row, _)| *other_row!=row && accepted(*other_row)){
+                    *shared.entry(*other_row).or_default() += 1;
+                }
+            }
+            shared.into_iter()
+                .filter(|(other_row, count)| *count as f64>=min_fraction*row_loci[row].len().min(row_loci[*other_row].len()) as f64)
+                .collect::<Vec<(usize, usize)>>()
+        };
+        let order: Vec<usize> = (0..item_strings.len()).sorted_by_key(|row| std::cmp::Reverse(lengths[*row])).collect();
+        let mut ranks: Vec<usize> = vec![0; order.len()];
+        for (rank, row) in order.iter().enumerate(){
+            ranks[*row] = rank;
+        }
+
+        let mut representatives: Vec<usize> = (0..item_strings.len()).collect();
+        match method{
+            ClusterMethod::SingleLinkage => {
+                let mut parents: Vec<usize> = (0..item_strings.len()).collect();
+                for row in 0..item_strings.len(){
+                    for (other_row, _) in linked_rows(row, &|other_row| other_row>row){
+                        let (root, other_root) = (find(&mut parents, row), find(&mut parents, other_row));
+                        parents[other_root] = root;
+                    }
+                }
+                let mut component_representatives: HashMap<usize, usize> = [].into_iter().collect();
+                for row in order.iter(){
+                    let root = find(&mut parents, *row);
+                    representatives[*row] = *component_representatives.entry(root).or_insert(*row);
+                }
+            },
+            ClusterMethod::GreedyCentroid => {
+                let mut is_centroid: Vec<bool> = vec![false; item_strings.len()];
+                for row in order.iter(){
+                    let centroid = linked_rows(*row, &|other_row| is_centroid[other_row]).into_iter()
+                        .max_by_key(|(centroid, count)| (*count, std::cmp::Reverse(ranks[*centroid])));
+                    match centroid{
+                        Some((centroid, _)) => representatives[*row] = centroid,
+                        None => is_centroid[*row] = true,
+                    }
+                }
+            },
+        }
+
+        let mut clusters: Vec<(usize, Vec<usize>)> = vec![];
+        let mut cluster_idx: HashMap<usize, usize> = [].into_iter().collect();
+        for row in order.iter().filter(|row| representatives[**row]==**row){
+            cluster_idx.insert(*row, clusters.len());
+            clusters.push((*row, vec![]));
+        }
+        for row in 0..item_strings.len(){
+            clusters[cluster_idx[&representatives[row]]].1.push(row);
+        }
+        clusters.into_iter()
+            .map(|(representative, members)| Cluster {
+                representative: item_strings[representative].0.clone(),
+                members: members.into_iter().map(|row| item_strings[row].0.clone()).collect(),
+            })
+            .collect()
+    }
+}
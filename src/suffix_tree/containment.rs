@@ -0,0 +1,99 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashSet as HashSet;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashSet;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Strings that are exact duplicates of other strings or substrings of longer ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContainmentReport<U>{
+    /// Groups of ids with identical strings, in insertion order.
+    pub duplicates: Vec<Vec<U>>,
+    /// `(contained id, container id, offset)` triples, with the first offset of the contained string in the container.
+    pub contained: Vec<(U, U, usize)>,
+}
+
+impl<U> ContainmentReport<U>
+where
+    U: Eq + Hash + Clone,
+{
+    /// Returns the ids that are dropped when deduplicating: every contained id and every duplicate but the first of its group.
+    pub fn redundant_ids(&self)->HashSet<U>{
+        self.duplicates.iter()
+            .flat_map(|group| group.iter().skip(1))
+            .chain(self.contained.iter().map(|(contained, _, _)| contained))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the strings that are exact duplicates of other strings and the strings that occur in longer strings.
+    ///
+    /// The full-length suffix of a string ends at its locus at depth equal to its length, and every suffix below that locus starts with the whole string. Strings sharing the leaf of their full-length suffix are duplicates.
+    /// Reverse strands and strings truncated below their own length are skipped, and strings inserted under the same id are not compared with each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "GATTACA".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "TTAC".chars().collect(), &0);
+    /// tree.insert("third".to_string(), "GATTACA".chars().collect(), &0);
+    /// let report = tree.containment_report();
+    /// assert_eq!(report.duplicates, vec![vec!["first".to_string(), "third".to_string()]]);
+    /// assert_eq!(report.contained[0], ("second".to_string(), "first".to_string(), 2));
+    /// ```
+    pub fn containment_report(&self)->ContainmentReport<U>{
+        let string_depths = self.string_depths();
+        let suffix_leaves = self.suffix_leaves();
+        let text_len = |string_id: &StringID| self.get_strings()[string_id].0.get_string().len()-1;
+        let compared = |string_id: &StringID| !self.reverse_strands.contains(string_id);
+
+        let mut duplicates: Vec<Vec<U>> = vec![];
+        let mut grouped: HashSet<StringID> = [].into_iter().collect();
+        let mut contained: Vec<(U, U, usize)> = vec![];
+        for string_id in self.get_strings().keys().filter(|string_id| compared(string_id)).sorted(){
+            let (item, max_depth) = &self.get_strings()[string_id];
+            let len = text_len(string_id);
+            if *max_depth<=len{
+                continue;
+            }
+            let mut locus = suffix_leaves[string_id][0];
+            while locus!=self.root && string_depths[*self.get_node(&locus).get_parent().expect("Non-root node has a parent")]>=len{
+                locus = *self.get_node(&locus).get_parent().expect("Non-root node has a parent");
+            }
+            let mut group: Vec<U> = vec![item.get_id().clone()];
+            for (other_id, starts) in self.string_occurrences_below(&[locus], len).into_iter().sorted_by_key(|(other_id, _)| *other_id){
+                let other_item = &self.get_strings()[&other_id].0;
+                if !compared(&other_id) || other_item.get_id()==item.get_id(){
+                    continue;
+                }
+                match text_len(&other_id)==len{
+                    true => if grouped.insert(other_id){
+                        group.push(other_item.get_id().clone());
+                    },
+                    false => contained.push((item.get_id().clone(), other_item.get_id().clone(), *starts.iter().min().expect("Occurrences are not empty"))),
+                }
+            }
+            if group.len()>1 && grouped.insert(*string_id){
+                duplicates.push(group);
+            }
+        }
+        ContainmentReport { duplicates, contained }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::suffix_node::node::*;
+use crate::alphabet::reverse_complement;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashSet as HashSet;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashSet;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::cmp::Ordering;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Sequence rules a primer or probe candidate has to satisfy. Bounds are inclusive.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DesignConstraints{
+    pub min_len: usize,
+    pub max_len: usize,
+    /// GC content as a fraction of the length.
+    pub min_gc: f64,
+    pub max_gc: f64,
+    /// Melting temperature in degrees Celsius, see [`melting_temperature`].
+    pub min_tm: f64,
+    pub max_tm: f64,
+    /// Longest allowed run of a single nucleotide.
+    pub max_homopolymer: usize,
+}
+
+impl Default for DesignConstraints{
+    /// Typical PCR primer rules.
+    fn default()->Self{
+        DesignConstraints { min_len: 18, max_len: 25, min_gc: 0.4, max_gc: 0.6, min_tm: 52.0, max_tm: 65.0, max_homopolymer: 4 }
+    }
+}
+
+/// A primer or probe candidate along with its sequence properties.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DesignCandidate<U>{
+    pub sequence: String,
+    pub gc_content: f64,
+    pub melting_temperature: f64,
+    pub longest_homopolymer: usize,
+    /// Total number of occurrences in the target strings, on the strand they were inserted with.
+    pub occurrences: usize,
+    /// Target ids containing the candidate, in insertion order.
+    pub targets: Vec<U>,
+}
+
+/// Returns the fraction of `G` and `C` nucleotides in a sequence, ignoring case.
+pub fn gc_content(sequence: &[char])->f64{
+    match sequence.is_empty(){
+        true => 0.0,
+        false => sequence.iter().filter(|c| matches!(c.to_ascii_uppercase(), 'G' | 'C')).count() as f64/sequence.len() as f64,
+    }
+}
+
+/// Returns the basic melting temperature of a sequence in degrees Celsius.
+/// 
+/// Sequences shorter than 14 nucleotides use the Wallace rule `2(A+T) + 4(G+C)`, longer ones use `64.9 + 41(G+C-16.4)/N`.
+pub fn melting_temperature(sequence: &[char])->f64{
+    let gc = sequence.iter().filter(|c| matches!(c.to_ascii_uppercase(), 'G' | 'C')).count() as f64;
+    let at = sequence.iter().filter(|c| matches!(c.to_ascii_uppercase(), 'A' | 'T' | 'U')).count() as f64;
+    match sequence.len()<14{
+        true => 2.0*at+4.0*gc,
+        false => 64.9+41.0*(gc-16.4)/sequence.len() as f64,
+    }
+}
+
+/// Returns the length of the longest run of a single character in a sequence, ignoring case.
+pub fn longest_homopolymer(sequence: &[char])->usize{
+    sequence.chunk_by(|c_1, c_2| c_1.eq_ignore_ascii_case(c_2)).map(|run| run.len()).max().unwrap_or(0)
+}
+
+impl<U> KGST<char, U>
+where
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns every substring that occurs in all target strings, in none of the off-target strings on either strand and satisfies the sequence constraints, ranked best first.
+    /// 
+    /// An empty target list selects every string that is not an off-target. Candidates are enumerated from the nodes whose subtrees contain every target and no off-target,
+    /// and candidates whose reverse complement occurs in an off-target are dropped as well. Only the targets are counted in the occurrences of a candidate.
+    /// then ranked by the distance of their melting temperature to the middle of the allowed range, the distance of their GC content to one half, their length and finally lexicographically.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::design::DesignConstraints;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("target_1".to_string(), "TTTTGCATGCAAAA".chars().collect(), &0);
+    /// tree.insert("target_2".to_string(), "CCCGCATGCAGGG".chars().collect(), &0);
+    /// tree.insert("off_target".to_string(), "GCATGGCATT".chars().collect(), &0);
+    /// let constraints = DesignConstraints { min_len: 6, max_len: 6, min_gc: 0.0, max_gc: 1.0, min_tm: 0.0, max_tm: 100.0, max_homopolymer: 2 };
+    /// let candidates = tree.design_candidates(&[], &["off_target".to_string()], &constraints);
+    /// assert_eq!(candidates.iter().map(|candidate| candidate.sequence.as_str()).collect::<Vec<&str>>(), vec!["GCATGC", "CATGCA"]);
+    /// ```
+    pub fn design_candidates(&self, targets: &[U], off_targets: &[U], constraints: &DesignConstraints)->Vec<DesignCandidate<U>>{
+        let off_targets: HashSet<usize> = self.item_rows(off_targets);
+        let targets: HashSet<usize> = match targets.is_empty(){
+            true => (0..self.item_string_ids().len()).filter(|row| !off_targets.contains(row)).collect(),
+            false => self.item_rows(targets),
+        };
+        if targets.is_empty(){
+            return vec![];
+        }
+        let (target_counts, off_target_counts) = self.group_counts(&targets, &off_targets);
+        let item_strings = self.item_string_ids();
+        let string_rows = self.string_rows();
+        let off_target_ids: HashSet<&U> = off_targets.iter().map(|row| &item_strings[*row].0).collect();
+        let string_depths = self.string_depths();
+        let optimal_tm = (constraints.min_tm+constraints.max_tm)/2.0;
+
+        let mut candidates: Vec<DesignCandidate<U>> = vec![];
+        for node_id in 0..self.num_nodes(){
+            if node_id==self.root || target_counts[node_id]<targets.len() || off_target_counts[node_id]>0{
+                continue;
+            }
+            let parent_depth = string_depths[node_id]-self.get_node(&node_id).get_edge_length();
+            let shortest = constraints.min_len.max(parent_depth+1);
+            let longest = constraints.max_len.min(self.node_text_depth(&node_id));
+            if shortest>longest{
+                continue;
+            }
+            let label: Vec<char> = self.node_substring(&node_id, longest);
+            for len in shortest..=longest{
+                let sequence = &label[..len];
+                let gc = gc_content(sequence);
+                let tm = melting_temperature(sequence);
+                let homopolymer = longest_homopolymer(sequence);
+                if gc<constraints.min_gc || gc>constraints.max_gc || tm<constraints.min_tm || tm>constraints.max_tm || homopolymer>constraints.max_homopolymer{
+                    continue;
+                }
+                let complement = reverse_complement(sequence);
+                if complement!=sequence && self.substring_match(&complement).keys().any(|item_id| off_target_ids.contains(item_id)){
+                    continue;
+                }
+                let target_occurrences: Vec<(usize, usize)> = self.string_occurrences_below(&[node_id], len).into_iter()
+                    .map(|(string_id, starts)| (string_rows[&string_id], starts.len()))
+                    .filter(|(row, _)| targets.contains(row))
+                    .collect();
+                candidates.push(DesignCandidate {
+                    sequence: sequence.iter().collect(),
+                    gc_content: gc,
+                    melting_temperature: tm,
+                    longest_homopolymer: homopolymer,
+                    occurrences: target_occurrences.iter().map(|(_, count)| count).sum(),
+                    targets: target_occurrences.iter().map(|(row, _)| *row).sorted().dedup().map(|row| item_strings[row].0.clone()).collect(),
+                });
+            }
+        }
+        candidates.sort_by(|a, b| (a.melting_temperature-optimal_tm).abs().partial_cmp(&(b.melting_temperature-optimal_tm).abs()).unwrap_or(Ordering::Equal)
+            .then((a.gc_content-0.5).abs().partial_cmp(&(b.gc_content-0.5).abs()).unwrap_or(Ordering::Equal))
+            .then(a.sequence.len().cmp(&b.sequence.len()))
+            .then(a.sequence.cmp(&b.sequence)));
+        candidates
+    }
+}
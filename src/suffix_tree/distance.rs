@@ -0,0 +1,247 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use super::KGST;
+use super::matrix::NodeWeight;
+use super::similarity::PairwiseMatrix;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// A k-mer as symbol indices, along with the node below its locus in the tree.
+pub(crate) type KmerLocus = (Vec<usize>, NodeID);
+
+/// Adds the products of the feature values of every pair of rows to a row-major kernel matrix.
+fn add_feature_products(kernel: &mut [f64], num_rows: usize, features: &[(usize, usize)]){
+    for (row_1, value_1) in features.iter(){
+        for (row_2, value_2) in features.iter(){
+            kernel[row_1*num_rows+row_2] += (*value_1 as f64)*(*value_2 as f64);
+        }
+    }
+}
+
+/// The k-mers of a tree and their counts, for walking the Hamming neighbourhoods of all k-mers at once.
+struct Neighbourhoods<'a>{
+    kmers: &'a [KmerLocus],
+    columns: &'a [Vec<(usize, usize)>],
+    alphabet_size: usize,
+    max_mismatches: usize,
+    num_rows: usize,
+}
+
+impl Neighbourhoods<'_>{
+    /// Extends every live k-mer by one position of a neighbour with symbol `symbol`, keeping the k-mers within `max_mismatches` of it.
+    fn extend(&self, live: &[(usize, usize)], position: usize, symbol: usize)->Vec<(usize, usize)>{
+        live.iter()
+            .map(|(kmer, mismatches)| (*kmer, mismatches+usize::from(self.kmers[*kmer].0[position]!=symbol)))
+            .filter(|(_, mismatches)| *mismatches<=self.max_mismatches)
+            .collect()
+    }
+
+    /// Walks all neighbours starting with a prefix of length `position`, given the k-mers within `max_mismatches` of the prefix, and adds the feature products of every complete neighbour to the kernel.
+    fn walk(&self, live: Vec<(usize, usize)>, position: usize, kernel: &mut [f64]){
+        if self.kmers[live[0].0].0.len()==position{
+            let mut features: HashMap<usize, usize> = [].into_iter().collect();
+            for (kmer, _) in live.iter(){
+                for (row, count) in self.columns[self.kmers[*kmer].1].iter(){
+                    *features.entry(*row).or_default() += count;
+                }
+            }
+            add_feature_products(kernel, self.num_rows, &features.into_iter().collect::<Vec<(usize, usize)>>());
+            return;
+        }
+        for symbol in 0..self.alphabet_size{
+            let next = self.extend(&live, position, symbol);
+            if !next.is_empty(){
+                self.walk(next, position+1, kernel);
+            }
+        }
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the k-mers of the tree with the nodes below their loci, and the number of distinct symbols in them.
+    pub(crate) fn kmer_loci(&self, k: usize)->(Vec<KmerLocus>, usize){
+        let string_depths = self.string_depths();
+        let mut alphabet: HashMap<T, usize> = [].into_iter().collect();
+        let mut kmers: Vec<KmerLocus> = vec![];
+        for node_id in 0..self.num_nodes(){
+            if node_id==self.root || self.node_text_depth(&node_id)<k{
+                continue;
+            }
+            let parent_id = self.get_node(&node_id).get_parent().expect("Non-root node has a parent");
+            if string_depths[*parent_id]>=k{
+                continue;
+            }
+            let kmer: Vec<usize> = self.node_path_label(&node_id)[..k].iter()
+                .map(|c| {
+                    let symbol = c.into_inner().expect("k-mers do not contain the terminal character");
+                    let next_symbol = alphabet.len();
+                    *alphabet.entry(symbol.clone()).or_insert(next_symbol)
+                })
+                .collect();
+            kmers.push((kmer, node_id));
+        }
+        (kmers, alphabet.len())
+    }
+
+    /// Returns the number of positions of every id and the mean of the matching statistics of every id against every other id.
+    ///
+    /// The matching statistic of a position of a string against another id is the depth of the deepest ancestor of its leaf that has a suffix of that id below it, which a single depth-first traversal keeps track of for all ids at once.
+    fn common_substring_lengths(&self)->(Vec<usize>, PairwiseMatrix<U>){
+        let incidence = self.incidence_matrix(NodeWeight::Binary);
+        let columns = incidence.columns();
+        let num_rows = incidence.num_rows();
+        let string_rows = self.string_rows();
+        let string_depths = self.string_depths();
+
+        enum Visit{
+            Enter(NodeID),
+            Exit(Vec<(usize, usize)>),
+        }
+        let mut deepest: Vec<usize> = vec![0; num_rows];
+        let mut sums: Vec<usize> = vec![0; num_rows*num_rows];
+        let mut positions: Vec<usize> = vec![0; num_rows];
+        let mut stack: Vec<Visit> = vec![Visit::Enter(self.root)];
+        while let Some(visit) = stack.pop(){
+            match visit{
+                Visit::Enter(node_id) => {
+                    stack.push(Visit::Exit(columns[node_id].iter().map(|(row, _)| (*row, deepest[*row])).collect()));
+                    for (row, _) in columns[node_id].iter(){
+                        deepest[*row] = string_depths[node_id];
+                    }
+                    for (string_id, starts) in self.get_node_data(&node_id).iter().filter(|_| self.get_node(&node_id).is_leaf()){
+                        let row = string_rows[string_id];
+                        let (string, max_depth) = &self.get_strings()[string_id];
+                        let text_len = string.get_string().len()-1;
+                        for start in starts.iter().filter(|start| **start<text_len){
+                            positions[row] += 1;
+                            for other_row in 0..num_rows{
+                                sums[row*num_rows+other_row] += match other_row==row{
+                                    true => (text_len-start).min(*max_depth),
+                                    false => (text_len-start).min(deepest[other_row]),
+                                };
+                            }
+                        }
+                    }
+                    for child_id in self.get_node(&node_id).get_children().values(){
+                        stack.push(Visit::Enter(*child_id));
+                    }
+                },
+                Visit::Exit(saved) => {
+                    for (row, depth) in saved{
+                        deepest[row] = depth;
+                    }
+                },
+            }
+        }
+        let averages = sums.into_iter().enumerate().map(|(idx, sum)| match positions[idx/num_rows]{
+            0 => 0.0,
+            num_positions => sum as f64/num_positions as f64,
+        }).collect();
+        (positions, PairwiseMatrix::new(incidence.row_ids().to_vec(), averages))
+    }
+
+    /// Returns the average common substring length `L(a, b)` of every pair of ids, the mean over the positions of `a` of the length of the longest prefix starting there that occurs in `b`.
+    ///
+    /// The matrix is not symmetric, `L(a, a)` is computed from the suffixes of `a` and every value is at most the depth of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abab".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "ba".chars().collect(), &0);
+    /// let lengths = tree.average_common_substrings();
+    /// assert_eq!(lengths.get(0, 1), 1.25);
+    /// assert_eq!(lengths.get(1, 0), 1.5);
+    /// ```
+    pub fn average_common_substrings(&self)->PairwiseMatrix<U>{
+        self.common_substring_lengths().1
+    }
+
+    /// Returns the symmetric average common substring (ACS) distance of every pair of ids.
+    ///
+    /// The distance of `a` to `b` is `ln|b|/L(a, b) - ln|a|/L(a, a)` and the returned matrix holds the mean of both directions. Ids without a common symbol are infinitely far apart.
+    pub fn acs_distances(&self)->PairwiseMatrix<U>{
+        let (positions, lengths) = self.common_substring_lengths();
+        // Without a common symbol L(a, b) is zero, and with a single position ln|b| is zero too, so both cases are settled before dividing.
+        let term = |num_positions: usize, average: f64| match num_positions{
+            1 => 0.0,
+            num_positions => (num_positions as f64).ln()/average,
+        };
+        let directed = |row: usize, col: usize| match lengths.get(row, col){
+            0.0 => f64::INFINITY,
+            shared => term(positions[col], shared)-term(positions[row], lengths.get(row, row)),
+        };
+        let values = (0..lengths.len()).flat_map(|row| (0..lengths.len()).map(move |col| (row, col)))
+            .map(|(row, col)| match row==col{
+                true => 0.0,
+                false => (directed(row, col)+directed(col, row))/2.0,
+            })
+            .collect();
+        PairwiseMatrix::new(lengths.ids().to_vec(), values)
+    }
+
+    /// Returns the k-spectrum kernel of every pair of ids, the dot product of their k-mer count vectors.
+    ///
+    /// Every k-mer is a locus at depth `k` in the tree, so its counts are read from the node below it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abab".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "bab".chars().collect(), &0);
+    /// let kernel = tree.spectrum_kernel(2);
+    /// assert_eq!(kernel.get(0, 0), 5.0);
+    /// assert_eq!(kernel.get(0, 1), 3.0);
+    /// ```
+    pub fn spectrum_kernel(&self, k: usize)->PairwiseMatrix<U>{
+        let incidence = self.incidence_matrix(NodeWeight::Occurrences);
+        let columns = incidence.columns();
+        let num_rows = incidence.num_rows();
+        let mut kernel: Vec<f64> = vec![0.0; num_rows*num_rows];
+        for (_, node_id) in self.kmer_loci(k).0{
+            add_feature_products(&mut kernel, num_rows, &columns[node_id]);
+        }
+        PairwiseMatrix::new(incidence.row_ids().to_vec(), kernel)
+    }
+
+    /// Returns the (k, m)-mismatch kernel of every pair of ids, where every k-mer also counts towards all k-mers within Hamming distance `max_mismatches`.
+    ///
+    /// The neighbourhoods are walked over the symbols of the k-mers in the tree, pruning prefixes that are too far from every k-mer, in parallel for every first symbol. With no mismatches, this is the spectrum kernel.
+    pub fn mismatch_kernel(&self, k: usize, max_mismatches: usize)->PairwiseMatrix<U>{
+        let incidence = self.incidence_matrix(NodeWeight::Occurrences);
+        let columns = incidence.columns();
+        let num_rows = incidence.num_rows();
+        let (kmers, alphabet_size) = self.kmer_loci(k);
+        let neighbourhoods = Neighbourhoods { kmers: &kmers, columns: &columns, alphabet_size, max_mismatches, num_rows };
+        let all_kmers: Vec<(usize, usize)> = (0..kmers.len()).map(|kmer| (kmer, 0)).collect();
+        let kernel: Vec<f64> = (0..alphabet_size).into_par_iter()
+            .map(|symbol| {
+                let mut kernel: Vec<f64> = vec![0.0; num_rows*num_rows];
+                let live = neighbourhoods.extend(&all_kmers, 0, symbol);
+                if !live.is_empty(){
+                    neighbourhoods.walk(live, 1, &mut kernel);
+                }
+                kernel
+            })
+            .reduce(|| vec![0.0; num_rows*num_rows], |kernel_1, kernel_2| kernel_1.iter().zip(kernel_2.iter()).map(|(value_1, value_2)| value_1+value_2).collect());
+        PairwiseMatrix::new(incidence.row_ids().to_vec(), kernel)
+    }
+}
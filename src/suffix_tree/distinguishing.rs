@@ -0,0 +1,162 @@
+use crate::suffix_node::node::*;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::{HashMap, HashSet};
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::cmp::Ordering;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// A substring distinguishing a target group of strings from a background group, along with its support in both groups.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DistinguishingSubstring<T, U>{
+    pub substring: Vec<T>,
+    pub target_count: usize,
+    pub background_count: usize,
+    pub targets: Vec<U>,
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the minimal substrings occurring in at least a fraction `min_target_fraction` of the target strings and in at most `max_background` of the background strings.
+    /// 
+    /// A substring is minimal if none of its proper substrings satisfies both thresholds. Ids that are not in the tree are ignored, so the fraction is taken over the targets present in the tree.
+    /// Strands and reading frames inserted under the same id count as a single string, and strings truncated below the length of a substring do not count for it.
+    /// Results are ordered by decreasing target count, then by length and then lexicographically, and list the targets containing them in insertion order.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcab".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "bcaab".chars().collect(), &0);
+    /// tree.insert("third".to_string(), "abaca".chars().collect(), &0);
+    /// let markers = tree.distinguishing_substrings(&["first".to_string(), "second".to_string()], &["third".to_string()], 1.0, 0);
+    /// assert_eq!(markers[0].substring, vec!['b', 'c']);
+    /// assert_eq!(markers[0].target_count, 2);
+    /// ```
+    pub fn distinguishing_substrings(&self, targets: &[U], background: &[U], min_target_fraction: f64, max_background: usize)->Vec<DistinguishingSubstring<T, U>>{
+        let item_strings = self.item_string_ids();
+        let string_rows = self.string_rows();
+        let targets: HashSet<usize> = self.item_rows(targets);
+        let background: HashSet<usize> = self.item_rows(background);
+        let min_targets = ((min_target_fraction*targets.len() as f64).ceil() as usize).max(1);
+        let (target_counts, background_counts) = self.group_counts(&targets, &background);
+        let string_depths = self.string_depths();
+        let max_query_len = self.node_counts().max_query_len;
+        // Above the smallest truncation depth, strings truncated below the length are left out, so the counts are taken from the document listing.
+        let counts = |node_id: &NodeID, len: usize| match len<=max_query_len{
+            true => (target_counts[*node_id], background_counts[*node_id]),
+            false => {
+                let rows: HashSet<usize> = self.listed_strings(node_id, len).into_iter().map(|string_id| string_rows[&string_id]).collect();
+                (rows.iter().filter(|row| targets.contains(row)).count(), rows.iter().filter(|row| background.contains(row)).count())
+            },
+        };
+
+        let mut distinguishing: Vec<(NodeID, usize, usize, usize)> = vec![];
+        for (node_id, node_targets) in target_counts.iter().enumerate(){
+            if node_id==self.root || *node_targets<min_targets{
+                continue;
+            }
+            let parent_id = *self.get_node(&node_id).get_parent().expect("Non-root node has a parent");
+            // Substrings ending on this edge share the counts of the node up to the smallest truncation depth, so only the shortest of them can be minimal.
+            // Longer ones can lose strings along the edge, so each of them is checked.
+            let shortest = string_depths[parent_id]+1;
+            let longest = self.node_text_depth(&node_id);
+            let split = longest.min(max_query_len);
+            let lens = (shortest<=split).then_some(shortest).into_iter().chain(shortest.max(split.saturating_add(1))..=longest);
+            let label = self.node_path_label(&node_id);
+            for len in lens{
+                let (target_count, background_count) = counts(&node_id, len);
+                if target_count<min_targets || background_count>max_background{
+                    continue;
+                }
+                // The longest proper prefix and suffix occur in at least as many targets, so they are only excluded by the background.
+                let prefix_node = if len>shortest {node_id} else {parent_id};
+                if prefix_node!=self.root && counts(&prefix_node, len-1).1<=max_background{
+                    continue;
+                }
+                let suffix: Vec<T> = label[1..len].iter().filter_map(|c| c.into_inner().cloned()).collect();
+                let suffix_background = match suffix.is_empty(){
+                    true => usize::MAX,
+                    false => self.get_pattern_node(&suffix).map(|suffix_node| counts(suffix_node, len-1).1).unwrap_or(0),
+                };
+                if suffix_background>max_background{
+                    distinguishing.push((node_id, len, target_count, background_count));
+                }
+            }
+        }
+
+        let mut distinguishing: Vec<DistinguishingSubstring<T, U>> = distinguishing.into_iter()
+            .map(|(node_id, len, target_count, background_count)| DistinguishingSubstring {
+                substring: self.node_substring(&node_id, len),
+                target_count,
+                background_count,
+                targets: self.listed_strings(&node_id, len).into_iter()
+                    .map(|string_id| string_rows[&string_id])
+                    .filter(|row| targets.contains(row))
+                    .sorted()
+                    .dedup()
+                    .map(|row| item_strings[row].0.clone())
+                    .collect(),
+            })
+            .collect();
+        distinguishing.sort_by(|a, b| b.target_count.cmp(&a.target_count)
+            .then(a.substring.len().cmp(&b.substring.len()))
+            .then(a.substring.partial_cmp(&b.substring).unwrap_or(Ordering::Equal)));
+        distinguishing
+    }
+
+    /// Returns the rows of the given ids in the insertion order of the ids, skipping ids that are not in the tree.
+    pub(crate) fn item_rows(&self, item_ids: &[U])->HashSet<usize>{
+        let item_ids: HashSet<&U> = item_ids.iter().collect();
+        self.item_string_ids().iter().enumerate()
+            .filter(|(_, (item_id, _))| item_ids.contains(item_id))
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Counts the distinct target and background ids below every node in a single bottom-up pass, merging the row sets of the children into the largest one.
+    ///
+    /// Groups are given as rows, so strands and reading frames inserted under the same id count once.
+    pub(crate) fn group_counts(&self, targets: &HashSet<usize>, background: &HashSet<usize>)->(Vec<usize>, Vec<usize>){
+        let string_rows = self.string_rows();
+        let mut target_counts: Vec<usize> = vec![0; self.num_nodes()];
+        let mut background_counts: Vec<usize> = vec![0; self.num_nodes()];
+        let mut row_sets: HashMap<NodeID, HashSet<usize>> = [].into_iter().collect();
+        for node_id in self.iter_nodes_post(){
+            let node = self.get_node(&node_id);
+            let node_rows: HashSet<usize> = match node.is_leaf(){
+                true => self.get_node_data(&node_id).keys()
+                    .map(|string_id| string_rows[string_id])
+                    .filter(|row| targets.contains(row) || background.contains(row))
+                    .collect(),
+                false => {
+                    let mut child_sets: Vec<HashSet<usize>> = node.get_children().values()
+                        .map(|child_id| row_sets.remove(child_id).unwrap_or_default())
+                        .collect();
+                    child_sets.sort_by_key(|set| std::cmp::Reverse(set.len()));
+                    let mut merged = child_sets.swap_remove(0);
+                    for child_set in child_sets{
+                        merged.extend(child_set);
+                    }
+                    merged
+                },
+            };
+            target_counts[node_id] = node_rows.iter().filter(|row| targets.contains(row)).count();
+            background_counts[node_id] = node_rows.len()-target_counts[node_id];
+            row_sets.insert(node_id, node_rows);
+        }
+        (target_counts, background_counts)
+    }
+}
@@ -0,0 +1,403 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use crate::utils::Enode;
+use super::KGST;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::io::{self, Write};
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Options controlling which part of a tree is exported and how it is labelled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DotOptions{
+    /// Node to export the subtree of, the root if `None`.
+    pub root: Option<NodeID>,
+    /// Number of edge levels exported below the subtree root, all if `None`.
+    pub max_depth: Option<usize>,
+    /// Number of characters shown of every edge label before it is shortened with `...`, all if `None`.
+    pub max_label_len: Option<usize>,
+    /// Draw the suffix links of internal nodes as dashed edges.
+    pub suffix_links: bool,
+    /// Annotate leaves with the `(id, start)` pairs of the suffixes ending in them.
+    pub leaf_data: bool,
+}
+
+impl Default for DotOptions{
+    fn default()->Self{
+        DotOptions { root: None, max_depth: None, max_label_len: None, suffix_links: true, leaf_data: true }
+    }
+}
+
+/// File formats for exporting a tree as a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NetworkFormat{
+    /// GraphML, as read by NetworkX, Cytoscape and Gephi.
+    GraphML,
+    /// GEXF 1.2, as read by Gephi and NetworkX.
+    Gexf,
+    /// Node-link JSON, as read by `networkx.node_link_graph`.
+    NodeLinkJson,
+}
+
+/// The kind of an edge of an exported network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeType{
+    /// An edge from a node to its child, labelled with the substring on the edge.
+    Tree,
+    /// An edge from an internal node to the node whose path label is its own without the first character.
+    SuffixLink,
+}
+
+impl Display for EdgeType{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            EdgeType::Tree => write!(f, "tree"),
+            EdgeType::SuffixLink => write!(f, "suffix_link"),
+        }
+    }
+}
+
+/// A node of an exported network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct NetworkNode{
+    id: NodeID,
+    string_depth: usize,
+    occurrences: usize,
+    strings: usize,
+    leaf: bool,
+}
+
+/// An edge of an exported network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct NetworkEdge{
+    source: NodeID,
+    target: NodeID,
+    #[serde(rename = "type")]
+    edge_type: EdgeType,
+    label: String,
+}
+
+/// Escapes a string for use in XML text and attributes.
+fn escape_xml(text: &str)->String{
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// Quotes a Newick label if it contains characters with a meaning in Newick.
+fn quote_newick(label: &str)->String{
+    match label.chars().any(|c| c.is_whitespace() || "()[]':;,".contains(c)){
+        true => format!("'{}'", label.replace('\'', "''")),
+        false => label.to_string(),
+    }
+}
+
+/// Escapes a string for use inside a double-quoted Graphviz attribute.
+fn escape_dot(label: &str)->String{
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Writes the tree as a Graphviz digraph, with nodes named by their ids and edges labelled by their substrings.
+    /// 
+    /// Children are written in node id order. Suffix links are only drawn between exported nodes.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::export::DotOptions;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abab".chars().collect(), &0);
+    /// let mut dot: Vec<u8> = vec![];
+    /// tree.to_dot(&mut dot, &DotOptions::default()).unwrap();
+    /// let dot = String::from_utf8(dot).unwrap();
+    /// assert!(dot.starts_with("digraph KGST {"));
+    /// assert!(dot.contains("[label=\"ab\"]"));
+    /// assert!(dot.contains("(first, 0)"));
+    /// ```
+    pub fn to_dot<W: Write>(&self, writer: &mut W, options: &DotOptions)->io::Result<()>{
+        let subtree_root = options.root.unwrap_or(self.root);
+        if subtree_root>=self.num_nodes(){
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Node {} does not exist", subtree_root)));
+        }
+        let string_order: Vec<StringID> = self.get_strings().keys().copied().sorted().collect();
+
+        writeln!(writer, "digraph KGST {{")?;
+        writeln!(writer, "    node [shape=circle, fontsize=10];")?;
+        let mut exported: Vec<bool> = vec![false; self.num_nodes()];
+        let mut stack: Vec<(NodeID, usize)> = vec![(subtree_root, 0)];
+        while let Some((node_id, depth)) = stack.pop(){
+            exported[node_id] = true;
+            let node = self.get_node(&node_id);
+            if node.is_leaf(){
+                let mut label: Vec<String> = vec![node_id.to_string()];
+                if options.leaf_data{
+                    label.extend(self.get_node_data(&node_id).iter()
+                        .sorted_by_key(|(string_id, _)| string_order.binary_search(string_id).ok())
+                        .flat_map(|(string_id, starts)| starts.iter().sorted().map(move |start| escape_dot(&format!("({}, {})", self.get_strings()[string_id].0.get_id(), start)))));
+                }
+                writeln!(writer, "    {} [shape=box, label=\"{}\"];", node_id, label.join("\\n"))?;
+            }
+            else{
+                writeln!(writer, "    {} [label=\"{}\"];", node_id, node_id)?;
+            }
+            if node_id!=subtree_root{
+                let parent_id = node.get_parent().expect("Non-root node has a parent");
+                let mut label: String = self.get_node_label(&node_id).iter().map(|c| c.to_string()).collect();
+                if let Some(max_label_len) = options.max_label_len.filter(|max_label_len| label.chars().count()>*max_label_len){
+                    label = format!("{}...", label.chars().take(max_label_len).collect::<String>());
+                }
+                writeln!(writer, "    {} -> {} [label=\"{}\"];", parent_id, node_id, escape_dot(&label))?;
+            }
+            if options.max_depth.is_none_or(|max_depth| depth<max_depth){
+                for child_id in node.get_children().values().sorted().rev(){
+                    stack.push((*child_id, depth+1));
+                }
+            }
+        }
+        if options.suffix_links{
+            for node_id in (0..self.num_nodes()).filter(|node_id| exported[*node_id] && node_id!=&self.root && !self.get_node(node_id).is_leaf()){
+                let link_id = self.suffix_links[&node_id];
+                if exported[link_id]{
+                    writeln!(writer, "    {} -> {} [style=dashed, color=gray, constraint=false];", node_id, link_id)?;
+                }
+            }
+        }
+        writeln!(writer, "}}")
+    }
+
+    /// Writes the tree as a directed network of tree edges and suffix links in the given format.
+    /// 
+    /// Nodes carry their string depth, number of occurrences, number of distinct strings and whether they are leaves.
+    /// Edges carry their type and, for tree edges, the substring on the edge.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::export::NetworkFormat;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abab".chars().collect(), &0);
+    /// let mut json: Vec<u8> = vec![];
+    /// tree.write_network(&mut json, NetworkFormat::NodeLinkJson).unwrap();
+    /// let json = String::from_utf8(json).unwrap();
+    /// assert!(json.contains("\"type\":\"suffix_link\""));
+    /// ```
+    pub fn write_network<W: Write>(&self, writer: &mut W, format: NetworkFormat)->io::Result<()>{
+        let (nodes, edges) = self.network();
+        match format{
+            NetworkFormat::GraphML => {
+                writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+                writeln!(writer, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+                writeln!(writer, "  <key id=\"string_depth\" for=\"node\" attr.name=\"string_depth\" attr.type=\"long\"/>")?;
+                writeln!(writer, "  <key id=\"occurrences\" for=\"node\" attr.name=\"occurrences\" attr.type=\"long\"/>")?;
+                writeln!(writer, "  <key id=\"strings\" for=\"node\" attr.name=\"strings\" attr.type=\"long\"/>")?;
+                writeln!(writer, "  <key id=\"leaf\" for=\"node\" attr.name=\"leaf\" attr.type=\"boolean\"/>")?;
+                writeln!(writer, "  <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>")?;
+                writeln!(writer, "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>")?;
+                writeln!(writer, "  <graph id=\"KGST\" edgedefault=\"directed\">")?;
+                for node in nodes.iter(){
+                    writeln!(writer, "    <node id=\"n{}\">", node.id)?;
+                    writeln!(writer, "      <data key=\"string_depth\">{}</data>", node.string_depth)?;
+                    writeln!(writer, "      <data key=\"occurrences\">{}</data>", node.occurrences)?;
+                    writeln!(writer, "      <data key=\"strings\">{}</data>", node.strings)?;
+                    writeln!(writer, "      <data key=\"leaf\">{}</data>", node.leaf)?;
+                    writeln!(writer, "    </node>")?;
+                }
+                for (edge_id, edge) in edges.iter().enumerate(){
+                    writeln!(writer, "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">", edge_id, edge.source, edge.target)?;
+                    writeln!(writer, "      <data key=\"type\">{}</data>", edge.edge_type)?;
+                    writeln!(writer, "      <data key=\"label\">{}</data>", escape_xml(&edge.label))?;
+                    writeln!(writer, "    </edge>")?;
+                }
+                writeln!(writer, "  </graph>")?;
+                writeln!(writer, "</graphml>")
+            },
+            NetworkFormat::Gexf => {
+                writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+                writeln!(writer, "<gexf xmlns=\"http://www.gexf.net/1.2draft\" version=\"1.2\">")?;
+                writeln!(writer, "  <graph mode=\"static\" defaultedgetype=\"directed\">")?;
+                writeln!(writer, "    <attributes class=\"node\">")?;
+                writeln!(writer, "      <attribute id=\"0\" title=\"string_depth\" type=\"long\"/>")?;
+                writeln!(writer, "      <attribute id=\"1\" title=\"occurrences\" type=\"long\"/>")?;
+                writeln!(writer, "      <attribute id=\"2\" title=\"strings\" type=\"long\"/>")?;
+                writeln!(writer, "      <attribute id=\"3\" title=\"leaf\" type=\"boolean\"/>")?;
+                writeln!(writer, "    </attributes>")?;
+                writeln!(writer, "    <attributes class=\"edge\">")?;
+                writeln!(writer, "      <attribute id=\"0\" title=\"type\" type=\"string\"/>")?;
+                writeln!(writer, "    </attributes>")?;
+                writeln!(writer, "    <nodes>")?;
+                for node in nodes.iter(){
+                    writeln!(writer, "      <node id=\"{}\" label=\"{}\">", node.id, node.id)?;
+                    writeln!(writer, "        <attvalues>")?;
+                    writeln!(writer, "          <attvalue for=\"0\" value=\"{}\"/>", node.string_depth)?;
+                    writeln!(writer, "          <attvalue for=\"1\" value=\"{}\"/>", node.occurrences)?;
+                    writeln!(writer, "          <attvalue for=\"2\" value=\"{}\"/>", node.strings)?;
+                    writeln!(writer, "          <attvalue for=\"3\" value=\"{}\"/>", node.leaf)?;
+                    writeln!(writer, "        </attvalues>")?;
+                    writeln!(writer, "      </node>")?;
+                }
+                writeln!(writer, "    </nodes>")?;
+                writeln!(writer, "    <edges>")?;
+                for (edge_id, edge) in edges.iter().enumerate(){
+                    writeln!(writer, "      <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{}\">", edge_id, edge.source, edge.target, escape_xml(&edge.label))?;
+                    writeln!(writer, "        <attvalues>")?;
+                    writeln!(writer, "          <attvalue for=\"0\" value=\"{}\"/>", edge.edge_type)?;
+                    writeln!(writer, "        </attvalues>")?;
+                    writeln!(writer, "      </edge>")?;
+                }
+                writeln!(writer, "    </edges>")?;
+                writeln!(writer, "  </graph>")?;
+                writeln!(writer, "</gexf>")
+            },
+            NetworkFormat::NodeLinkJson => {
+                let network = serde_json::json!({
+                    "directed": true,
+                    "multigraph": false,
+                    "graph": {},
+                    "nodes": nodes,
+                    "links": edges,
+                });
+                serde_json::to_writer(&mut *writer, &network)?;
+                writeln!(writer)
+            },
+        }
+    }
+
+    /// Returns the nodes of the tree in id order, followed by its tree edges and the suffix links of its internal nodes.
+    fn network(&self)->(Vec<NetworkNode>, Vec<NetworkEdge>){
+        let string_depths = self.string_depths();
+        let nodes: Vec<NetworkNode> = (0..self.num_nodes())
+            .map(|node_id| {
+                let (occurrences, strings) = self.substring_counts(&node_id, string_depths[node_id]);
+                NetworkNode {
+                    id: node_id,
+                    string_depth: string_depths[node_id],
+                    occurrences,
+                    strings,
+                    leaf: self.get_node(&node_id).is_leaf(),
+                }
+            })
+            .collect();
+        let tree_edges = (0..self.num_nodes())
+            .filter(|node_id| node_id!=&self.root)
+            .map(|node_id| NetworkEdge {
+                source: *self.get_node(&node_id).get_parent().expect("Non-root node has a parent"),
+                target: node_id,
+                edge_type: EdgeType::Tree,
+                label: self.get_node_label(&node_id).iter().map(|c| c.to_string()).collect(),
+            });
+        let suffix_links = (0..self.num_nodes())
+            .filter(|node_id| node_id!=&self.root && !self.get_node(node_id).is_leaf())
+            .map(|node_id| NetworkEdge { source: node_id, target: self.suffix_links[&node_id], edge_type: EdgeType::SuffixLink, label: String::new() });
+        (nodes, tree_edges.chain(suffix_links).collect())
+    }
+
+    /// Returns one [`Enode`] per node of the tree in node id order, with its children by edge symbol, the ids of the strings passing through it in insertion order and its suffix link.
+    pub fn export_all_nodes(&self)->Vec<Enode<T, U>>{
+        (0..self.num_nodes()).map(|node_id| self.export_node(&node_id)).collect()
+    }
+
+    /// Writes every node of the tree as an [`Enode`] JSON object on its own line, in node id order, without collecting the nodes first.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abab".chars().collect(), &0);
+    /// let mut jsonl: Vec<u8> = vec![];
+    /// tree.write_nodes_jsonl(&mut jsonl).unwrap();
+    /// let jsonl = String::from_utf8(jsonl).unwrap();
+    /// assert_eq!(jsonl.lines().count(), tree.num_nodes());
+    /// assert!(jsonl.lines().next().unwrap().contains("\"node_id\":0"));
+    /// ```
+    pub fn write_nodes_jsonl<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        for node_id in 0..self.num_nodes(){
+            serde_json::to_writer(&mut *writer, &self.export_node(&node_id))?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    fn export_node(&self, node_id: &NodeID)->Enode<T, U>{
+        let node = self.get_node(node_id);
+        let children = node.get_children().iter()
+            .map(|(edge, child_id)| (edge.into_inner().cloned().expect("Terminal Character cannot be unwrapped!"), *child_id))
+            .collect();
+        let slink = match node_id!=&self.root && !node.is_leaf(){
+            true => Some(self.suffix_links[node_id]),
+            false => None,
+        };
+        Enode::new(children, self.node_strings(node_id, self.string_depths()[*node_id]), slink, *node_id)
+    }
+
+    /// Writes the topology of the tree in Newick format, with edge label lengths as branch lengths.
+    /// 
+    /// Leaves are named by the `id_start` pairs of the suffixes ending in them, joined by `|`, and internal nodes are labelled by their ids if `internal_labels` is set.
+    /// The tree is written while it is traversed, so the Newick string is never held in memory.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "aa".chars().collect(), &0);
+    /// let mut newick: Vec<u8> = vec![];
+    /// tree.to_newick(&mut newick, false).unwrap();
+    /// assert_eq!(String::from_utf8(newick).unwrap(), "((first_0:2,first_1:1):1,first_2:1);\n");
+    /// ```
+    pub fn to_newick<W: Write>(&self, writer: &mut W, internal_labels: bool)->io::Result<()>{
+        enum Visit{
+            Open(NodeID),
+            Separator,
+            Close(NodeID),
+        }
+        let string_order: Vec<StringID> = self.get_strings().keys().copied().sorted().collect();
+        let branch_length = |node_id: &NodeID| match node_id==&self.root{
+            true => String::new(),
+            false => format!(":{}", self.get_node(node_id).get_edge_length()),
+        };
+        let mut stack: Vec<Visit> = vec![Visit::Open(self.root)];
+        while let Some(visit) = stack.pop(){
+            match visit{
+                Visit::Open(node_id) if self.get_node(&node_id).is_leaf() => {
+                    let name = self.get_node_data(&node_id).iter()
+                        .sorted_by_key(|(string_id, _)| string_order.binary_search(string_id).ok())
+                        .flat_map(|(string_id, starts)| starts.iter().sorted().map(move |start| format!("{}_{}", self.get_strings()[string_id].0.get_id(), start)))
+                        .join("|");
+                    write!(writer, "{}{}", quote_newick(&name), branch_length(&node_id))?;
+                },
+                Visit::Open(node_id) => {
+                    write!(writer, "(")?;
+                    stack.push(Visit::Close(node_id));
+                    for (idx, child_id) in self.get_node(&node_id).get_children().values().sorted().rev().enumerate(){
+                        if idx>0{
+                            stack.push(Visit::Separator);
+                        }
+                        stack.push(Visit::Open(*child_id));
+                    }
+                },
+                Visit::Separator => write!(writer, ",")?,
+                Visit::Close(node_id) => {
+                    write!(writer, ")")?;
+                    if internal_labels{
+                        write!(writer, "{}", node_id)?;
+                    }
+                    write!(writer, "{}", branch_length(&node_id))?;
+                },
+            }
+        }
+        writeln!(writer, ";")
+    }
+}
@@ -0,0 +1,104 @@
+use crate::suffix_node::node::*;
+use super::KGST;
+use super::matrix::{IncidenceMatrix, NodeWeight};
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::io::{self, Write};
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Substring counts of every id, along with the substring of every column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureVectors<T, U>{
+    features: Vec<Vec<T>>,
+    counts: IncidenceMatrix<U>,
+}
+
+impl<T, U> FeatureVectors<T, U>
+where
+    T: Display,
+    U: Display,
+{
+    /// Returns the substring of every column.
+    pub fn features(&self)->&[Vec<T>]{
+        &self.features
+    }
+
+    /// Returns the sparse id-by-feature count matrix.
+    pub fn counts(&self)->&IncidenceMatrix<U>{
+        &self.counts
+    }
+
+    /// Writes the feature dictionary as a tsv of column indices and substrings.
+    pub fn write_features<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        writeln!(writer, "column\tsubstring")?;
+        for (col, feature) in self.features.iter().enumerate(){
+            writeln!(writer, "{}\t{}", col, feature.iter().join(""))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the counts in libsvm format with 1-based feature indices. Every line is labelled by its row index and ends with the id as a comment.
+    pub fn write_libsvm<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        for (row, id) in self.counts.row_ids().iter().enumerate(){
+            let (cols, values) = self.counts.row(row);
+            write!(writer, "{}", row)?;
+            for (col, value) in cols.iter().zip(values.iter()){
+                write!(writer, " {}:{}", col+1, value)?;
+            }
+            writeln!(writer, " # {}", id)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the number of occurrences in every id of the substrings of length `min_len` to `max_len` that are path labels of internal nodes contained in at least `min_support` ids.
+    ///
+    /// Substrings ending on the same edge have the same counts, so every internal node gives one feature, its path label shortened to `max_len`. Features are ordered by node id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcab".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "cabd".chars().collect(), &0);
+    /// let vectors = tree.feature_vectors(2, 3, 2);
+    /// assert_eq!(vectors.features().len(), 2);
+    /// let ab = vectors.features().iter().position(|feature| feature==&vec!['a', 'b']).unwrap();
+    /// let (cols, counts) = vectors.counts().row(0);
+    /// assert_eq!(counts[cols.iter().position(|col| *col==ab).unwrap()], 2);
+    /// ```
+    pub fn feature_vectors(&self, min_len: usize, max_len: usize, min_support: usize)->FeatureVectors<T, U>{
+        let incidence = self.incidence_matrix(NodeWeight::Occurrences);
+        let columns = incidence.columns();
+        let string_depths = self.string_depths();
+        let mut features: Vec<Vec<T>> = vec![];
+        let mut rows: Vec<Vec<(usize, usize)>> = vec![vec![]; incidence.num_rows()];
+        for node_id in 0..self.num_nodes(){
+            let node = self.get_node(&node_id);
+            if node_id==self.root || node.is_leaf() || columns[node_id].len()<min_support || string_depths[node_id]<min_len{
+                continue;
+            }
+            let parent_id = node.get_parent().expect("Non-root node has a parent");
+            if string_depths[*parent_id]>=max_len{
+                continue;
+            }
+            for (row, count) in columns[node_id].iter(){
+                rows[*row].push((features.len(), *count));
+            }
+            features.push(self.node_path_label(&node_id).iter()
+                .take(max_len)
+                .map(|c| c.into_inner().cloned().expect("Internal nodes do not contain the terminal character"))
+                .collect());
+        }
+        FeatureVectors { counts: IncidenceMatrix::from_rows(incidence.row_ids().to_vec(), features.len(), rows), features }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BTreeMap;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// How the frequency of a substring is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Frequency{
+    /// Total number of occurrences over all strings.
+    Occurrences,
+    /// Number of distinct ids whose strings contain the substring.
+    Strings,
+}
+
+/// A substring of the indexed strings along with its frequency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FrequentSubstring<T, U>{
+    pub substring: Vec<T>,
+    pub count: usize,
+    pub strings: Vec<U>,
+}
+
+/// A distinct k-mer of the indexed strings along with its total count and its count in every string containing it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KmerCount<T, U>
+where
+    U: Eq + Hash
+{
+    pub kmer: Vec<T>,
+    pub count: usize,
+    pub string_counts: HashMap<U, usize>,
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the k most frequent substrings with a length in `[min_len, max_len]`, ordered by decreasing count.
+    /// 
+    /// Substrings with equal counts are ordered by length and then lexicographically, and substrings tied with the k-th one are dropped in that order.
+    /// Every substring ending on the same edge shares the frequency of the node below it, so only the nodes are ranked before the winning edges are expanded.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::frequency::Frequency;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcabxabcd".chars().collect(), &0);
+    /// let top = tree.top_k_substrings(1, 2, 3, Frequency::Occurrences);
+    /// assert_eq!(top[0].substring, vec!['a', 'b']);
+    /// assert_eq!(top[0].count, 3);
+    /// ```
+    pub fn top_k_substrings(&self, k: usize, min_len: usize, max_len: usize, frequency: Frequency)->Vec<FrequentSubstring<T, U>>{
+        let min_len = min_len.max(1);
+        let string_depths = self.string_depths();
+        let max_query_len = self.node_counts().max_query_len;
+        // Every node with at least one substring in the length range, with its count and the range of lengths it covers.
+        // Lengths above the smallest truncation depth can have different counts on the same edge, so they get a range each.
+        let candidates: Vec<(usize, NodeID, usize, usize)> = (0..self.num_nodes())
+            .filter(|node_id| node_id!=&self.root)
+            .flat_map(|node_id| {
+                let parent_depth = string_depths[node_id]-self.get_node(&node_id).get_edge_length();
+                let shortest = min_len.max(parent_depth+1);
+                let longest = max_len.min(self.node_text_depth(&node_id));
+                let split = longest.min(max_query_len);
+                (shortest<=split).then_some((shortest, split)).into_iter()
+                    .chain((shortest.max(split.saturating_add(1))..=longest).map(|len| (len, len)))
+                    .map(move |(shortest, longest)| (self.node_frequency(&node_id, longest, frequency), node_id, shortest, longest))
+                    .filter(|(count, _, _, _)| *count>0)
+            })
+            .sorted_by_key(|(count, node_id, _, _)| (Reverse(*count), *node_id))
+            .collect();
+
+        let mut selected: Vec<(usize, NodeID, usize)> = vec![];
+        for (count, node_id, shortest, longest) in candidates{
+            if selected.len()>=k && selected.last().is_some_and(|(last_count, _, _)| *last_count>count){
+                break;
+            }
+            selected.extend((shortest..=longest).map(|len| (count, node_id, len)));
+        }
+
+        selected.into_iter()
+            .map(|(count, node_id, len)| (count, node_id, self.node_substring(&node_id, len)))
+            .sorted_by(|a, b| b.0.cmp(&a.0).then(a.2.len().cmp(&b.2.len())).then(a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal)))
+            .take(k)
+            .map(|(count, node_id, substring)| FrequentSubstring { strings: self.node_strings(&node_id, substring.len()), substring, count })
+            .collect()
+    }
+
+    /// Returns an iterator over every distinct k-mer of the indexed strings, read from the nodes whose incoming edge spans string depth k.
+    /// 
+    /// K-mers are yielded in node id order. Trees truncated below k contain no k-mers.
+    /// The strings containing a k-mer are read from the document listing and each of their counts takes a binary search, so no leaves are visited.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcabxabcd".chars().collect(), &3);
+    /// let abc = tree.iter_kmers(3).find(|kmer| kmer.kmer==vec!['a', 'b', 'c']).unwrap();
+    /// assert_eq!(abc.count, 2);
+    /// assert_eq!(tree.iter_kmers(3).count(), 7);
+    /// ```
+    pub fn iter_kmers(&self, k: usize)->impl Iterator<Item = KmerCount<T, U>> + '_{
+        self.kmer_nodes(k).into_iter().filter_map(move |node_id| {
+            let mut string_counts: HashMap<U, usize> = [].into_iter().collect();
+            for string_id in self.listed_strings(&node_id, k){
+                *string_counts.entry(self.get_strings()[&string_id].0.get_id().clone()).or_default() += self.document_listing().occurrences(&node_id, &string_id);
+            }
+            let count = string_counts.values().sum();
+            (count>0).then(|| KmerCount { kmer: self.node_substring(&node_id, k), count, string_counts })
+        })
+    }
+
+    /// Returns the k-mer spectrum of the indexed strings, mapping each count to the number of distinct k-mers occurring that many times.
+    pub fn kmer_spectrum(&self, k: usize)->BTreeMap<usize, usize>{
+        let mut spectrum: BTreeMap<usize, usize> = BTreeMap::new();
+        for node_id in self.kmer_nodes(k){
+            match self.substring_counts(&node_id, k).0{
+                0 => continue,
+                count => *spectrum.entry(count).or_default() += 1,
+            }
+        }
+        spectrum
+    }
+
+    /// Returns the nodes whose incoming edge spans string depth k without passing the terminal character.
+    fn kmer_nodes(&self, k: usize)->Vec<NodeID>{
+        let string_depths = self.string_depths();
+        (0..self.num_nodes())
+            .filter(|node_id| node_id!=&self.root && k>0)
+            .filter(|node_id| string_depths[*node_id]-self.get_node(node_id).get_edge_length()<k && self.node_text_depth(node_id)>=k)
+            .collect()
+    }
+
+    /// Returns the frequency of the prefix of length `len` of the path label of a node.
+    pub(crate) fn node_frequency(&self, node_id: &NodeID, len: usize, frequency: Frequency)->usize{
+        let (occurrences, strings) = self.substring_counts(node_id, len);
+        match frequency{
+            Frequency::Occurrences => occurrences,
+            Frequency::Strings => strings,
+        }
+    }
+
+    /// Returns the first `len` characters of the path label of a node.
+    pub(crate) fn node_substring(&self, node_id: &NodeID, len: usize)->Vec<T>{
+        self.node_path_label(node_id)[..len].iter()
+            .map(|c| c.into_inner().cloned().expect("Terminal Character cannot be unwrapped!"))
+            .collect()
+    }
+
+    /// Returns the distinct ids of the strings containing the prefix of length `len` of the path label of a node, in insertion order.
+    pub(crate) fn node_strings(&self, node_id: &NodeID, len: usize)->Vec<U>{
+        self.listed_strings(node_id, len).into_iter()
+            .sorted()
+            .map(|string_id| self.get_strings()[&string_id].0.get_id().clone())
+            .unique()
+            .collect()
+    }
+}
@@ -0,0 +1,229 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::io::{self, Write};
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Weights of the entries of a string-by-node incidence matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NodeWeight{
+    /// 1 for every node whose path label occurs in the string.
+    Binary,
+    /// Number of occurrences of the path label of the node in the string.
+    Occurrences,
+    /// Length of the edge label ending in the node, so nodes with empty edge labels such as the root have no entries.
+    EdgeLength,
+}
+
+/// A string-by-node incidence matrix in compressed sparse row (CSR) form.
+///
+/// Row `i` belongs to the `i`-th id in insertion order and column `j` to node `j`. Only nonzero entries are stored and the column indices of every row are sorted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IncidenceMatrix<U>{
+    row_ids: Vec<U>,
+    num_cols: usize,
+    row_offsets: Vec<usize>,
+    col_indices: Vec<NodeID>,
+    values: Vec<usize>,
+}
+
+impl<U> IncidenceMatrix<U>
+where
+    U: Display,
+{
+    /// Creates a matrix from the nonzero `(column, value)` entries of every row.
+    pub(crate) fn from_rows(row_ids: Vec<U>, num_cols: usize, rows: Vec<Vec<(usize, usize)>>)->Self{
+        let mut row_offsets: Vec<usize> = vec![0];
+        let mut col_indices: Vec<usize> = vec![];
+        let mut values: Vec<usize> = vec![];
+        for mut row in rows{
+            row.sort_unstable();
+            for (col, value) in row{
+                col_indices.push(col);
+                values.push(value);
+            }
+            row_offsets.push(col_indices.len());
+        }
+        IncidenceMatrix { row_ids, num_cols, row_offsets, col_indices, values }
+    }
+
+    /// Returns the ids the rows belong to.
+    pub fn row_ids(&self)->&[U]{
+        &self.row_ids
+    }
+
+    pub fn num_rows(&self)->usize{
+        self.row_ids.len()
+    }
+
+    pub fn num_cols(&self)->usize{
+        self.num_cols
+    }
+
+    /// Returns the number of stored (nonzero) entries.
+    pub fn nnz(&self)->usize{
+        self.values.len()
+    }
+
+    pub fn row_offsets(&self)->&[usize]{
+        &self.row_offsets
+    }
+
+    pub fn col_indices(&self)->&[NodeID]{
+        &self.col_indices
+    }
+
+    pub fn values(&self)->&[usize]{
+        &self.values
+    }
+
+    /// Returns the column indices and values of the nonzero entries of a row.
+    pub fn row(&self, row: usize)->(&[NodeID], &[usize]){
+        let (start, end) = (self.row_offsets[row], self.row_offsets[row+1]);
+        (&self.col_indices[start..end], &self.values[start..end])
+    }
+
+    /// Returns the rows and values of the nonzero entries of every column.
+    pub(crate) fn columns(&self)->Vec<Vec<(usize, usize)>>{
+        let mut columns: Vec<Vec<(usize, usize)>> = vec![vec![]; self.num_cols];
+        for row in 0..self.num_rows(){
+            let (cols, values) = self.row(row);
+            for (col, value) in cols.iter().zip(values.iter()){
+                columns[*col].push((row, *value));
+            }
+        }
+        columns
+    }
+
+    /// Writes the matrix in Matrix Market coordinate format with 1-based indices, listing the row ids in the comment lines.
+    pub fn write_matrix_market<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        writeln!(writer, "%%MatrixMarket matrix coordinate integer general")?;
+        for (row, row_id) in self.row_ids.iter().enumerate(){
+            writeln!(writer, "% row {} {}", row+1, row_id)?;
+        }
+        writeln!(writer, "{} {} {}", self.num_rows(), self.num_cols, self.nnz())?;
+        for row in 0..self.num_rows(){
+            let (cols, values) = self.row(row);
+            for (col, value) in cols.iter().zip(values.iter()){
+                writeln!(writer, "{} {} {}", row+1, col+1, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the matrix as little-endian `u64`s: the number of rows, columns and nonzero entries, followed by the row offsets, column indices and values.
+    pub fn write_csr<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        for value in [self.num_rows(), self.num_cols, self.nnz()].iter()
+            .chain(self.row_offsets.iter())
+            .chain(self.col_indices.iter())
+            .chain(self.values.iter()){
+            writer.write_all(&(*value as u64).to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the ids of the tree in insertion order along with the string ids inserted under each of them.
+    ///
+    /// Strands and reading frames inserted under the same id are grouped together.
+    pub(crate) fn item_string_ids(&self)->Vec<(U, Vec<StringID>)>{
+        let mut rows: HashMap<U, usize> = [].into_iter().collect();
+        let mut item_strings: Vec<(U, Vec<StringID>)> = vec![];
+        for string_id in self.get_strings().keys().sorted(){
+            let item_id = self.get_strings()[string_id].0.get_id();
+            match rows.get(item_id){
+                Some(row) => item_strings[*row].1.push(*string_id),
+                None => {
+                    rows.insert(item_id.clone(), item_strings.len());
+                    item_strings.push((item_id.clone(), vec![*string_id]));
+                },
+            }
+        }
+        item_strings
+    }
+
+    /// Returns the row of every string, which is the position of its id in the insertion order of the ids.
+    pub(crate) fn string_rows(&self)->HashMap<StringID, usize>{
+        self.item_string_ids().into_iter().enumerate()
+            .flat_map(|(row, (_, string_ids))| string_ids.into_iter().map(move |string_id| (string_id, row)))
+            .collect()
+    }
+
+    /// Returns the string-by-node incidence matrix of the tree, where a string is incident to every node whose path label occurs in it within its truncation depth.
+    ///
+    /// Strings are counted in a single bottom-up pass that merges the per-string occurrence counts of the children into the largest one, so the work is proportional to the number of nonzero entries up to a logarithmic factor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::matrix::NodeWeight;
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "aa".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "b".chars().collect(), &0);
+    /// let incidence = tree.incidence_matrix(NodeWeight::Occurrences);
+    /// assert_eq!(incidence.num_rows(), 2);
+    /// assert_eq!(incidence.row(0).1.iter().max(), Some(&3));
+    /// assert_eq!(incidence.row(1).1, &[2, 1, 1]);
+    /// ```
+    pub fn incidence_matrix(&self, weight: NodeWeight)->IncidenceMatrix<U>{
+        let item_strings = self.item_string_ids();
+        let string_rows = self.string_rows();
+        let string_depths = self.string_depths();
+        let mut rows: Vec<Vec<(NodeID, usize)>> = vec![vec![]; item_strings.len()];
+        let mut string_counts: HashMap<NodeID, HashMap<StringID, usize>> = [].into_iter().collect();
+        for node_id in self.iter_nodes_post(){
+            let node = self.get_node(&node_id);
+            let node_counts: HashMap<StringID, usize> = match node.is_leaf(){
+                true => self.get_node_data(&node_id).iter()
+                    .map(|(string_id, starts)| (*string_id, starts.len()))
+                    .collect(),
+                false => {
+                    let mut child_counts: Vec<HashMap<StringID, usize>> = node.get_children().values()
+                        .map(|child_id| string_counts.remove(child_id).unwrap_or_default())
+                        .collect();
+                    child_counts.sort_by_key(|counts| std::cmp::Reverse(counts.len()));
+                    let mut merged = child_counts.swap_remove(0);
+                    for counts in child_counts{
+                        for (string_id, count) in counts{
+                            *merged.entry(string_id).or_default() += count;
+                        }
+                    }
+                    merged
+                },
+            };
+            // Strings truncated below the path label of the node can still have occurrences stored in the leaves below it.
+            let mut row_counts: HashMap<usize, usize> = [].into_iter().collect();
+            for (string_id, count) in node_counts.iter().filter(|(string_id, _)| self.get_strings()[*string_id].1>=string_depths[node_id]){
+                *row_counts.entry(string_rows[string_id]).or_default() += count;
+            }
+            for (row, count) in row_counts{
+                let value = match weight{
+                    NodeWeight::Binary => 1,
+                    NodeWeight::Occurrences => count,
+                    NodeWeight::EdgeLength => node.get_edge_length(),
+                };
+                if value>0{
+                    rows[row].push((node_id, value));
+                }
+            }
+            string_counts.insert(node_id, node_counts);
+        }
+        IncidenceMatrix::from_rows(item_strings.into_iter().map(|(item_id, _)| item_id).collect(), self.num_nodes(), rows)
+    }
+}
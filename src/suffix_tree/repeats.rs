@@ -0,0 +1,219 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use crate::alphabet::reverse_complement;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashSet as HashSet;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashSet;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// A maximal tandem repeat of an indexed string: `copies` full copies of a primitive unit of length `period` starting at `start`.
+/// 
+/// The repeat may continue with a partial copy of the unit, which is not counted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct TandemRepeat<U>{
+    pub string: U,
+    pub start: usize,
+    pub period: usize,
+    pub copies: usize,
+}
+
+/// How the right arm of an inverted repeat mirrors the left arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Reflection{
+    /// The right arm is the reverse of the left arm.
+    Reverse,
+    /// The right arm is the IUPAC reverse complement of the left arm, as in hairpins and restriction sites.
+    ReverseComplement,
+}
+
+/// An inverted repeat of an indexed string: a left arm of length `arm` starting at `start`, a spacer of length `gap` and the mirrored right arm.
+/// 
+/// Palindromes are the inverted repeats without a spacer, or with a single character spacer for odd length palindromes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct InvertedRepeat<U>{
+    pub string: U,
+    pub start: usize,
+    pub arm: usize,
+    pub gap: usize,
+}
+
+impl<U> InvertedRepeat<U>{
+    /// Returns the length of the repeat including both arms and the spacer.
+    pub fn len(&self)->usize{
+        2*self.arm+self.gap
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.len()==0
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the maximal tandem repeats of every string with a primitive period in `[min_period, max_period]` and at least `min_copies` full copies, sorted by string, start and period.
+    /// 
+    /// Reverse complement strands added by [`KGST::insert_both_strands`] are skipped. A `max_period` of 0 allows any period. For every period p only every p-th position is probed,
+    /// extending to the right with LCE queries on the tree and to the left with LCE queries on a tree of the reversed string, so a string takes O(n/p) constant time queries per period and O(n log n) time over all periods up to `max_period`.
+    /// LCE queries that reach the truncation depth are continued by direct comparison.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::repeats::TandemRepeat;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "xabcabcabcay".chars().collect(), &0);
+    /// let repeats = tree.tandem_repeats(2, 0, 3);
+    /// assert_eq!(repeats, vec![TandemRepeat { string: "first".to_string(), start: 1, period: 3, copies: 3 }]);
+    /// ```
+    pub fn tandem_repeats(&self, min_period: usize, max_period: usize, min_copies: usize)->Vec<TandemRepeat<U>>{
+        let min_period = min_period.max(1);
+        let min_copies = min_copies.max(2);
+        let mut repeats: Vec<TandemRepeat<U>> = vec![];
+        for string_id in self.get_strings().keys().filter(|string_id| !self.reverse_strands.contains(string_id)).sorted(){
+            let (item, _) = &self.get_strings()[string_id];
+            let string = item.get_string();
+            let text_len = string.len()-1;
+            let max_period = match max_period {0 => text_len/2, _ => max_period.min(text_len/2)};
+            // The characters before two positions are the characters after their mirrored positions in the reversed string.
+            let mut reversed_tree: KGST<T, usize> = KGST::new(self.terminal_character.clone());
+            reversed_tree.insert(0, string.iter().filter_map(|c| c.into_inner().cloned()).take(text_len).collect_vec().into_iter().rev().collect(), &0);
+            // A run whose unit is a power of a shorter unit spans exactly the run of that shorter period, so smaller periods are probed as well to tell them apart.
+            let mut spans: HashSet<(usize, usize)> = [].into_iter().collect();
+            for period in 1..=max_period{
+                let mut last_end: Option<usize> = None;
+                for probe in (0..text_len-period).step_by(period){
+                    let right = self.string_lce(string_id, probe, string_id, probe+period);
+                    let left = reversed_tree.string_lce(&0, text_len-probe, &0, text_len-probe-period).min(period-1);
+                    if left+right<period{
+                        continue;
+                    }
+                    // Later probes in the same run only see part of its left extension, so runs are told apart by their end.
+                    let (start, end) = (probe-left, probe+period+right);
+                    if last_end==Some(end){
+                        continue;
+                    }
+                    last_end = Some(end);
+                    let copies = (end-start)/period;
+                    if !spans.insert((start, end)) || period<min_period || copies<min_copies{
+                        continue;
+                    }
+                    repeats.push(TandemRepeat { string: item.get_id().clone(), start, period, copies });
+                }
+            }
+        }
+        let string_order: Vec<&U> = self.get_strings().keys().sorted().map(|string_id| self.get_strings()[string_id].0.get_id()).collect();
+        repeats.sort_by_key(|repeat| (string_order.iter().position(|item_id| *item_id==&repeat.string), repeat.start, repeat.period));
+        repeats
+    }
+
+    /// Returns the length of the longest common extension of two positions of the strings of the tree, continuing by direct comparison past the truncation depth.
+    pub(crate) fn string_lce(&self, string_1: &StringID, start_1: usize, string_2: &StringID, start_2: usize)->usize{
+        let (item_1, max_depth_1) = &self.get_strings()[string_1];
+        let (item_2, max_depth_2) = &self.get_strings()[string_2];
+        let (chars_1, chars_2) = (item_1.get_string(), item_2.get_string());
+        let mut lce = self.suffix_lcp(string_1, start_1, string_2, start_2);
+        if lce>=*max_depth_1.min(max_depth_2){
+            while start_1+lce<chars_1.len()-1 && start_2+lce<chars_2.len()-1 && chars_1[start_1+lce]==chars_2[start_2+lce]{
+                lce += 1;
+            }
+        }
+        lce
+    }
+}
+
+impl<U> KGST<char, U>
+where
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the maximal palindromes of every string with a length of at least `min_len`, sorted by string, start and spacer.
+    /// 
+    /// Palindromes under [`Reflection::ReverseComplement`] always have even length, since no nucleotide is its own complement.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::repeats::Reflection;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "TTGAATTCAA".chars().collect(), &0);
+    /// let palindromes = tree.maximal_palindromes(Reflection::ReverseComplement, 6);
+    /// assert_eq!((palindromes[0].start, palindromes[0].len()), (0, 10));
+    /// ```
+    pub fn maximal_palindromes(&self, reflection: Reflection, min_len: usize)->Vec<InvertedRepeat<U>>{
+        let gaps = match reflection{
+            Reflection::Reverse => 0..=1,
+            Reflection::ReverseComplement => 0..=0,
+        };
+        let mut palindromes = self.inverted_repeats_with(reflection, gaps, 1, false);
+        palindromes.retain(|palindrome| palindrome.len()>=min_len);
+        palindromes
+    }
+
+    /// Returns the maximal inverted repeats of every string with arms of at least `min_arm` characters separated by a spacer of `min_gap` to `max_gap` characters, sorted by string, start and spacer.
+    /// 
+    /// A repeat is maximal if its arms can be extended neither outwards nor into the spacer.
+    /// The arms are found with LCE queries between a string and its mirror image. The mirror strand is read from the tree if it was built with [`KGST::insert_both_strands`],
+    /// and from a tree of the string and its mirror image otherwise.
+    pub fn inverted_repeats(&self, reflection: Reflection, min_arm: usize, min_gap: usize, max_gap: usize)->Vec<InvertedRepeat<U>>{
+        self.inverted_repeats_with(reflection, min_gap..=max_gap, min_arm.max(1), true)
+    }
+
+    fn inverted_repeats_with(&self, reflection: Reflection, gaps: RangeInclusive<usize>, min_arm: usize, inward_maximal: bool)->Vec<InvertedRepeat<U>>{
+        let mut repeats: Vec<InvertedRepeat<U>> = vec![];
+        for string_id in self.get_strings().keys().filter(|string_id| !self.reverse_strands.contains(string_id)).sorted(){
+            let item = &self.get_strings()[string_id].0;
+            let arms = match self.reverse_complements.get(string_id).filter(|_| reflection==Reflection::ReverseComplement){
+                Some(mirror_id) => self.mirrored_arms(string_id, mirror_id, gaps.clone(), min_arm, inward_maximal),
+                None => {
+                    let forward: Vec<char> = item.get_string().iter().filter_map(|c| c.into_inner().cloned()).take(item.get_string().len()-1).collect();
+                    let mirror: Vec<char> = match reflection{
+                        Reflection::Reverse => forward.iter().rev().cloned().collect(),
+                        Reflection::ReverseComplement => reverse_complement(&forward),
+                    };
+                    let mut mirror_tree: KGST<char, usize> = KGST::new(self.terminal_character);
+                    mirror_tree.insert(0, forward, &0);
+                    mirror_tree.insert(1, mirror, &0);
+                    mirror_tree.mirrored_arms(&0, &1, gaps.clone(), min_arm, inward_maximal)
+                },
+            };
+            repeats.extend(arms.into_iter().sorted_by_key(|(start, arm, gap)| (*start, *gap, *arm)).map(|(start, arm, gap)| InvertedRepeat { string: item.get_id().clone(), start, arm, gap }));
+        }
+        repeats
+    }
+
+    /// Returns the start, arm length and spacer length of the maximal inverted repeats of a string, given the id of its mirror image.
+    /// 
+    /// The left arm ending before position i and the right arm starting after a spacer of length g are compared by the LCE of position i+g of the string and position n-i of the mirror.
+    fn mirrored_arms(&self, string_id: &StringID, mirror_id: &StringID, gaps: RangeInclusive<usize>, min_arm: usize, inward_maximal: bool)->Vec<(usize, usize, usize)>{
+        let forward = self.get_strings()[string_id].0.get_string();
+        let mirror = self.get_strings()[mirror_id].0.get_string();
+        let text_len = forward.len()-1;
+        let mut arms: Vec<(usize, usize, usize)> = vec![];
+        for center in 1..text_len{
+            for gap in gaps.clone().take_while(|gap| center+gap<text_len){
+                if inward_maximal && gap>=2 && forward[center]==mirror[text_len-center-gap]{
+                    continue;
+                }
+                let arm = self.string_lce(string_id, center+gap, mirror_id, text_len-center);
+                if arm>=min_arm{
+                    arms.push((center-arm, arm, gap));
+                }
+            }
+        }
+        arms
+    }
+}
@@ -0,0 +1,195 @@
+use crate::suffix_node::node::*;
+use super::KGST;
+use super::matrix::NodeWeight;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use std::io::{self, Write};
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// Similarity measures between the rows of a string-by-node incidence matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Similarity{
+    /// Number of shared nodes over the number of nodes incident to either string.
+    Jaccard,
+    /// Sum of the smaller weight over the sum of the larger weight of every node.
+    WeightedJaccard,
+    /// Cosine of the angle between the weight vectors.
+    Cosine,
+}
+
+/// A square matrix of values between every pair of a list of ids.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairwiseMatrix<U>{
+    ids: Vec<U>,
+    values: Vec<f64>,
+}
+
+impl<U> PairwiseMatrix<U>
+where
+    U: Display,
+{
+    /// Creates a matrix from the ids and the row-major values of its rows.
+    pub fn new(ids: Vec<U>, values: Vec<f64>)->Self{
+        assert_eq!(values.len(), ids.len()*ids.len(), "Matrix must be square!");
+        PairwiseMatrix { ids, values }
+    }
+
+    pub fn ids(&self)->&[U]{
+        &self.ids
+    }
+
+    pub fn len(&self)->usize{
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.ids.is_empty()
+    }
+
+    pub fn get(&self, row: usize, col: usize)->f64{
+        self.values[row*self.len()+col]
+    }
+
+    /// Returns the values of a row.
+    pub fn row(&self, row: usize)->&[f64]{
+        &self.values[row*self.len()..(row+1)*self.len()]
+    }
+
+    /// Converts a matrix of similarities in `[0, 1]` to a matrix of distances `1-s`.
+    pub fn to_distances(&self)->PairwiseMatrix<U>
+    where
+        U: Clone,
+    {
+        PairwiseMatrix { ids: self.ids.clone(), values: self.values.iter().map(|value| 1.0-value).collect() }
+    }
+
+    /// Converts a kernel matrix to the Euclidean distances between the normalized feature vectors, `sqrt(2-2k(a,b)/sqrt(k(a,a)k(b,b)))`.
+    pub fn kernel_distances(&self)->PairwiseMatrix<U>
+    where
+        U: Clone,
+    {
+        let values = (0..self.len()).cartesian_product(0..self.len()).map(|(row, col)| {
+            let norm = (self.get(row, row)*self.get(col, col)).sqrt();
+            let normalized = match (row==col, norm>0.0){
+                (true, _) => 1.0,
+                (false, true) => self.get(row, col)/norm,
+                (false, false) => 0.0,
+            };
+            (2.0-2.0*normalized).max(0.0).sqrt()
+        }).collect();
+        PairwiseMatrix { ids: self.ids.clone(), values }
+    }
+
+    /// Writes the matrix as a tsv with the ids as row and column names.
+    pub fn write_tsv<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        writeln!(writer, "\t{}", self.ids.iter().join("\t"))?;
+        for (row, id) in self.ids.iter().enumerate(){
+            writeln!(writer, "{}\t{}", id, self.row(row).iter().join("\t"))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the matrix as a square PHYLIP distance matrix.
+    ///
+    /// Whitespace in ids is replaced by `_` and ids are padded to 10 characters, so ids of up to 9 characters give a strict PHYLIP file and longer ones a relaxed PHYLIP file.
+    pub fn write_phylip<W: Write>(&self, writer: &mut W)->io::Result<()>{
+        writeln!(writer, "{}", self.len())?;
+        for (row, id) in self.ids.iter().enumerate(){
+            let name: String = id.to_string().chars().map(|c| match c.is_whitespace(){
+                true => '_',
+                false => c,
+            }).collect();
+            write!(writer, "{:<10}", name)?;
+            for value in self.row(row){
+                write!(writer, " {:.6}", value)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the similarity of two sparse rows with sorted column indices.
+fn row_similarity(a: (&[NodeID], &[usize]), b: (&[NodeID], &[usize]), similarity: Similarity)->f64{
+    let (mut shared, mut union, mut min_sum, mut max_sum, mut dot) = (0, 0, 0, 0, 0.0);
+    for entry in a.0.iter().zip(a.1.iter()).merge_join_by(b.0.iter().zip(b.1.iter()), |(a_col, _), (b_col, _)| a_col.cmp(b_col)){
+        union += 1;
+        match entry{
+            itertools::EitherOrBoth::Both((_, a_value), (_, b_value)) => {
+                shared += 1;
+                min_sum += a_value.min(b_value);
+                max_sum += a_value.max(b_value);
+                dot += (*a_value as f64)*(*b_value as f64);
+            },
+            itertools::EitherOrBoth::Left((_, value)) | itertools::EitherOrBoth::Right((_, value)) => max_sum += value,
+        }
+    }
+    let norm = |row: &[usize]| row.iter().map(|value| (*value as f64).powi(2)).sum::<f64>().sqrt();
+    let (numerator, denominator) = match similarity{
+        Similarity::Jaccard => (shared as f64, union as f64),
+        Similarity::WeightedJaccard => (min_sum as f64, max_sum as f64),
+        Similarity::Cosine => (dot, norm(a.1)*norm(b.1)),
+    };
+    // Two strings without any weighted nodes are identical.
+    match denominator>0.0{
+        true => numerator/denominator,
+        false => 1.0,
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the pairwise similarities of the given ids, or of all ids in insertion order if `ids` is empty, computed over the rows of the incidence matrix weighted by `weight`.
+    ///
+    /// With [`NodeWeight::EdgeLength`] weights, the weighted Jaccard similarity is the fraction of the distinct substrings of two strings that they share. Ids that are not in the tree are ignored and the pairs are computed in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::matrix::NodeWeight;
+    /// use generalized_suffix_tree::suffix_tree::similarity::Similarity;
+    ///
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "ab".chars().collect(), &0);
+    /// tree.insert("second".to_string(), "ab".chars().collect(), &0);
+    /// tree.insert("third".to_string(), "cd".chars().collect(), &0);
+    /// let similarities = tree.similarity_matrix(&[], Similarity::Jaccard, NodeWeight::EdgeLength);
+    /// assert_eq!(similarities.get(0, 1), 1.0);
+    /// assert_eq!(similarities.get(0, 2), 0.2);
+    /// ```
+    pub fn similarity_matrix(&self, ids: &[U], similarity: Similarity, weight: NodeWeight)->PairwiseMatrix<U>{
+        let incidence = self.incidence_matrix(weight);
+        let selected_rows: Vec<usize> = match ids.is_empty(){
+            true => (0..incidence.num_rows()).collect(),
+            false => {
+                let rows: HashMap<&U, usize> = incidence.row_ids().iter().enumerate().map(|(row, id)| (id, row)).collect();
+                ids.iter().filter_map(|id| rows.get(id).copied()).unique().collect()
+            },
+        };
+        let rows: Vec<(&[NodeID], &[usize])> = selected_rows.iter().map(|row| incidence.row(*row)).collect();
+        let upper: Vec<Vec<f64>> = (0..rows.len()).into_par_iter()
+            .map(|i| (i+1..rows.len()).map(|j| row_similarity(rows[i], rows[j], similarity)).collect())
+            .collect();
+        let mut values: Vec<f64> = vec![1.0; rows.len()*rows.len()];
+        for (i, row_values) in upper.into_iter().enumerate(){
+            for (offset, value) in row_values.into_iter().enumerate(){
+                let j = i+1+offset;
+                values[i*rows.len()+j] = value;
+                values[j*rows.len()+i] = value;
+            }
+        }
+        PairwiseMatrix::new(selected_rows.into_iter().map(|row| incidence.row_ids()[row].clone()).collect(), values)
+    }
+}
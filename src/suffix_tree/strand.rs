@@ -0,0 +1,97 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use crate::alphabet::reverse_complement;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use serde::Serialize;
+
+/// The strand of a nucleotide string a match was found on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum Strand{
+    Forward,
+    Reverse,
+}
+
+impl Display for Strand{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self{
+            Strand::Forward => write!(f, "+"),
+            Strand::Reverse => write!(f, "-"),
+        }
+    }
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns the strand held by a string of the tree.
+    pub(crate) fn string_strand(&self, string_id: &StringID)->Strand{
+        match self.reverse_strands.contains(string_id){
+            true => Strand::Reverse,
+            false => Strand::Forward,
+        }
+    }
+}
+
+impl<U> KGST<char, U>
+where
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Inserts a nucleotide string along with its IUPAC reverse complement, both under the same id.
+    /// 
+    /// Queries that are not strand aware, such as [`KGST::substring_match`], report positions of both strands together. Use [`KGST::stranded_match`] to tell them apart.
+    pub fn insert_both_strands(&mut self, k: U, v: Vec<char>, max_depth: &usize){
+        let reverse = reverse_complement(&v);
+        let forward_id: StringID = self.strings.len();
+        self.insert(k.clone(), v, max_depth);
+        let reverse_id: StringID = self.strings.len();
+        self.insert(k, reverse, max_depth);
+        self.reverse_strands.insert(reverse_id);
+        self.reverse_complements.insert(forward_id, reverse_id);
+    }
+
+    /// Retrieves all strings that contain the input slice on either strand, along with the strand and the forward start position of every match, sorted by position and strand.
+    /// 
+    /// A match on the reverse strand at forward position p means the reverse complement of the query occurs at p on the forward string.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::strand::Strand;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert_both_strands("first".to_string(), "GATTACCA".chars().collect(), &0);
+    /// let matches = tree.stranded_match(&['G', 'G', 'T']);
+    /// assert_eq!(matches.get("first").unwrap(), &vec![(4, Strand::Reverse)]);
+    /// ```
+    pub fn stranded_match(&self, s:&[char])->HashMap<U, Vec<(usize, Strand)>>{
+        let mut matches: HashMap<U, Vec<(usize, Strand)>> = [].into_iter().collect();
+        let node_id = match self.get_pattern_node(s){
+            None => return matches,
+            Some(node_id) => *node_id,
+        };
+        for (string_id, starts) in self.string_occurrences_below(&[node_id], s.len()){
+            let (item, _) = &self.get_strings()[&string_id];
+            let strand = self.string_strand(&string_id);
+            let text_len = item.get_string().len()-1;
+            matches.entry(item.get_id().clone()).or_default().extend(starts.into_iter().map(|start| match strand{
+                Strand::Forward => (start, strand),
+                Strand::Reverse => (text_len-start-s.len(), strand),
+            }));
+        }
+        for positions in matches.values_mut(){
+            positions.sort();
+        }
+        matches
+    }
+}
@@ -0,0 +1,100 @@
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use crate::alphabet::{reverse_complement, GeneticCode};
+use crate::suffix_tree::strand::Strand;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use serde::Serialize;
+
+/// One of the six reading frames of a nucleotide sequence. The offset is the number of nucleotides skipped before the first codon on the strand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ReadingFrame{
+    pub strand: Strand,
+    pub offset: usize,
+}
+
+impl Display for ReadingFrame{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.strand, self.offset+1)
+    }
+}
+
+/// A match in a translated reading frame, given by its half-open range of forward strand nucleotide coordinates and its frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct TranslatedHit{
+    pub start: usize,
+    pub end: usize,
+    pub frame: ReadingFrame,
+}
+
+impl<U> KGST<char, U>
+where
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Translates a nucleotide string into its six reading frames with the given genetic code and inserts every frame under the same id.
+    /// 
+    /// Frames shorter than a codon are skipped. Use [`KGST::translated_match`] to report matches in nucleotide coordinates.
+    pub fn insert_translated(&mut self, k: U, v: Vec<char>, genetic_code: GeneticCode, max_depth: &usize){
+        let reverse = reverse_complement(&v);
+        for (strand, sequence) in [(Strand::Forward, &v), (Strand::Reverse, &reverse)]{
+            for offset in 0..3{
+                let protein = genetic_code.translate(sequence.get(offset..).unwrap_or_default());
+                if protein.is_empty(){
+                    continue;
+                }
+                self.insert(k.clone(), protein, max_depth);
+                self.reading_frames.insert(self.strings.len()-1, (ReadingFrame { strand, offset }, v.len()));
+            }
+        }
+    }
+
+    /// Retrieves all strings with a translated reading frame containing the input protein slice, along with every match in nucleotide coordinates, sorted by position and frame.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::strand::Strand;
+    /// use generalized_suffix_tree::suffix_tree::translate::{ReadingFrame, TranslatedHit};
+    /// use generalized_suffix_tree::alphabet::GeneticCode;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert_translated("contig".to_string(), "AATGGCCATTAA".chars().collect(), GeneticCode::Standard, &0);
+    /// let hits = tree.translated_match(&['M', 'A', 'I']);
+    /// assert_eq!(hits.get("contig").unwrap(), &vec![
+    ///     TranslatedHit { start: 0, end: 9, frame: ReadingFrame { strand: Strand::Reverse, offset: 0 } },
+    ///     TranslatedHit { start: 1, end: 10, frame: ReadingFrame { strand: Strand::Forward, offset: 1 } },
+    /// ]);
+    /// ```
+    pub fn translated_match(&self, s:&[char])->HashMap<U, Vec<TranslatedHit>>{
+        let mut hits: HashMap<U, Vec<TranslatedHit>> = [].into_iter().collect();
+        let node_id = match self.get_pattern_node(s){
+            None => return hits,
+            Some(node_id) => *node_id,
+        };
+        for (string_id, starts) in self.string_occurrences_below(&[node_id], s.len()){
+            let (frame, nucleotide_len) = match self.reading_frames.get(&string_id){
+                None => continue,
+                Some(reading_frame) => *reading_frame,
+            };
+            let item_id = self.get_strings()[&string_id].0.get_id().clone();
+            hits.entry(item_id).or_default().extend(starts.into_iter().map(|start| {
+                let (frame_start, frame_end) = (frame.offset+3*start, frame.offset+3*(start+s.len()));
+                match frame.strand{
+                    Strand::Forward => TranslatedHit { start: frame_start, end: frame_end, frame },
+                    Strand::Reverse => TranslatedHit { start: nucleotide_len-frame_end, end: nucleotide_len-frame_start, frame },
+                }
+            }));
+        }
+        for string_hits in hits.values_mut(){
+            string_hits.sort();
+        }
+        hits
+    }
+}
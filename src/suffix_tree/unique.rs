@@ -0,0 +1,128 @@
+use crate::suffix_node::node::*;
+use crate::data::tree_item::TreeItem as OtherTreeItem;
+use super::KGST;
+
+#[cfg(feature = "non_crypto_hash")]
+use fxhash::FxHashMap as HashMap;
+#[cfg(not(feature = "non_crypto_hash"))]
+use std::collections::HashMap;
+
+use std::fmt::{Display, Debug};
+use std::hash::Hash;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// The set of strings a substring must be unique in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Uniqueness{
+    /// The substring occurs once over all indexed strings.
+    Collection,
+    /// The substring occurs once in its own string, regardless of the other strings.
+    String,
+}
+
+/// A substring of an indexed string, given by the id of the string, its start position and its length.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct UniqueSubstring<U>{
+    pub string: U,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl<T, U> KGST<T, U>
+where
+    T: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize + PartialOrd,
+    U: Display + Debug + Eq + PartialEq + Hash + Clone + Serialize,
+{
+    /// Returns, for every string and every position in it, the length of the shortest substring starting there that is unique, or `None` if there is no such substring within the string and its truncation depth.
+    /// 
+    /// In [`Uniqueness::Collection`] mode the length is the first depth on the path to the suffix leaf where a single occurrence is left.
+    /// In [`Uniqueness::String`] mode it is one more than the longest common prefix with the neighbouring suffixes of the same string in depth first leaf order, found with LCA queries.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use generalized_suffix_tree::suffix_tree::KGST;
+    /// use generalized_suffix_tree::suffix_tree::unique::Uniqueness;
+    /// 
+    /// let mut tree: KGST<char, String> = KGST::new('$');
+    /// tree.insert("first".to_string(), "abcab".chars().collect(), &0);
+    /// let sus = tree.shortest_unique_substrings(Uniqueness::Collection);
+    /// assert_eq!(sus.get("first").unwrap(), &vec![Some(3), Some(2), Some(1), None, None]);
+    /// ```
+    pub fn shortest_unique_substrings(&self, uniqueness: Uniqueness)->HashMap<U, Vec<Option<usize>>>{
+        self.get_strings().keys()
+            .map(|string_id| (self.get_strings()[string_id].0.get_id().clone(), self.string_shortest_unique_substrings(string_id, uniqueness)))
+            .collect()
+    }
+
+    /// Returns every shortest unique substring of minimum length over all strings, sorted by string and start position.
+    pub fn globally_shortest_unique_substrings(&self, uniqueness: Uniqueness)->Vec<UniqueSubstring<U>>{
+        let lengths: Vec<(StringID, Vec<Option<usize>>)> = self.get_strings().keys()
+            .sorted()
+            .map(|string_id| (*string_id, self.string_shortest_unique_substrings(string_id, uniqueness)))
+            .collect();
+        let min_len = match lengths.iter().flat_map(|(_, string_lengths)| string_lengths.iter().flatten()).min(){
+            None => return vec![],
+            Some(min_len) => *min_len,
+        };
+        lengths.iter()
+            .flat_map(|(string_id, string_lengths)| string_lengths.iter().enumerate()
+                .filter(|(_, len)| **len==Some(min_len))
+                .map(|(start, _)| UniqueSubstring { string: self.get_strings()[string_id].0.get_id().clone(), start, len: min_len }))
+            .collect()
+    }
+
+    fn string_shortest_unique_substrings(&self, string_id: &StringID, uniqueness: Uniqueness)->Vec<Option<usize>>{
+        let (item, max_depth) = &self.get_strings()[string_id];
+        // The stored string ends with the terminal character.
+        let string_len = item.get_string().len()-1;
+        let leaves = &self.suffix_leaves()[string_id][..string_len];
+        let shared_prefixes: Vec<Option<usize>> = match uniqueness{
+            Uniqueness::Collection => leaves.iter().map(|leaf| self.collection_shared_prefix(leaf)).collect(),
+            Uniqueness::String => self.string_shared_prefixes(string_id, leaves),
+        };
+        shared_prefixes.into_iter().enumerate()
+            .map(|(start, shared_prefix)| shared_prefix.map(|x| x+1).filter(|len| *len<=string_len-start && len<=max_depth))
+            .collect()
+    }
+
+    /// Returns the length of the longest prefix of a suffix that also occurs elsewhere in the collection, or `None` if the whole suffix does.
+    ///
+    /// Prefixes ending on the same edge share the count of the node below it up to the smallest truncation depth, so only the first one is checked there, while longer prefixes are checked one length at a time.
+    fn collection_shared_prefix(&self, leaf: &NodeID)->Option<usize>{
+        let string_depths = self.string_depths();
+        let max_query_len = self.node_counts().max_query_len;
+        for node_id in self.iter_path_pre(leaf).skip(1){
+            let parent_depth = string_depths[node_id]-self.get_node(&node_id).get_edge_length();
+            let lens = std::iter::once(parent_depth+1).chain((parent_depth+2).max(max_query_len.saturating_add(1))..=string_depths[node_id]);
+            for len in lens{
+                if self.substring_counts(&node_id, len).0==1{
+                    return Some(len-1);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns, for the suffixes of a string, the length of the longest prefix shared with another suffix of the same string.
+    ///
+    /// The deepest ancestor of a leaf with another suffix of the string below it covers a contiguous range of the depth first order, so the longest shared prefix is found among the neighbours in that order, even though it is not lexicographic.
+    fn string_shared_prefixes(&self, string_id: &StringID, leaves: &[NodeID])->Vec<Option<usize>>{
+        let document_listing = self.document_listing();
+        let mut shared_prefixes: Vec<Option<usize>> = vec![Some(0); leaves.len()];
+        let leaf_order: Vec<usize> = (0..leaves.len()).sorted_by_key(|start| document_listing.leaf_rank(&leaves[*start])).collect();
+        for (start_1, start_2) in leaf_order.into_iter().tuple_windows(){
+            if leaves[start_1]==leaves[start_2]{
+                shared_prefixes[start_1] = None;
+                shared_prefixes[start_2] = None;
+                continue;
+            }
+            let lcp = self.suffix_lcp(string_id, start_1, string_id, start_2);
+            for start in [start_1, start_2]{
+                shared_prefixes[start] = shared_prefixes[start].map(|x| x.max(lcp));
+            }
+        }
+        shared_prefixes
+    }
+}
@@ -31,4 +31,23 @@ where
                 slink
             }
         }
-}
\ No newline at end of file
+
+    /// Returns the ids of the children of the node, keyed by the first symbol of their edges.
+    pub fn children(&self)->&HashMap<T, usize>{
+        &self.children
+    }
+
+    /// Returns the ids of the strings passing through the node.
+    pub fn data(&self)->&[U]{
+        &self.data
+    }
+
+    pub fn node_id(&self)->usize{
+        self.node_id
+    }
+
+    /// Returns the suffix link of the node, which only internal nodes other than the root have.
+    pub fn slink(&self)->Option<usize>{
+        self.slink
+    }
+}
@@ -1,4 +1,139 @@
 use generalized_suffix_tree::suffix_tree::*;
+use generalized_suffix_tree::data::pattern::*;
+use generalized_suffix_tree::suffix_tree::frequency::*;
+use generalized_suffix_tree::suffix_tree::unique::*;
+use generalized_suffix_tree::suffix_tree::design::*;
+use generalized_suffix_tree::suffix_tree::strand::*;
+use generalized_suffix_tree::suffix_tree::repeats::*;
+use generalized_suffix_tree::suffix_tree::translate::*;
+use generalized_suffix_tree::suffix_tree::export::*;
+use generalized_suffix_tree::suffix_tree::matrix::*;
+use generalized_suffix_tree::suffix_tree::similarity::*;
+use generalized_suffix_tree::suffix_tree::features::*;
+use generalized_suffix_tree::suffix_tree::cluster::*;
+use generalized_suffix_tree::suffix_tree::containment::*;
+use generalized_suffix_tree::suffix_node::node::SuffixNode;
+use generalized_suffix_tree::data::tree_item::TreeItem as OtherTreeItem;
+use generalized_suffix_tree::alphabet::{reverse_complement, GeneticCode};
+use itertools::Itertools;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Builds a tree from strings paired with their truncation depths, using the index of every string as its id.
+fn build_tree(string_set: &[(Vec<char>, usize)])->KGST<char, String>{
+    let mut tree: KGST<char, String> = KGST::new('$');
+    for (item_idx, (item, max_depth)) in string_set.iter().enumerate(){
+        tree.insert(item_idx.to_string(), item.clone(), max_depth);
+    }
+    tree
+}
+
+/// Returns the sorted start positions of a query in every string containing it, leaving out strings truncated below the query.
+fn brute_force_occurrences(string_set: &[(Vec<char>, usize)], query: &[char])->std::collections::HashMap<String, Vec<usize>>{
+    string_set.iter().enumerate()
+        .filter(|(_, (_, max_depth))| *max_depth==0 || query.len()<=*max_depth)
+        .map(|(item_idx, (item, _))| (item_idx.to_string(), (0..item.len()).filter(|start| item[*start..].starts_with(query)).collect::<Vec<usize>>()))
+        .filter(|(_, starts)| !starts.is_empty())
+        .collect()
+}
+
+/// Three overlapping DNA strings shared by the query tests.
+fn gattaca_strings()->Vec<Vec<char>>{
+    ["GATTACAGATTACAGGATCC", "CCGATCGATTACCAGATTA", "TTACAGA"].iter().map(|string| string.chars().collect()).collect()
+}
+
+/// The GATTACA strings, all truncated at the same depth.
+fn gattaca_set(max_depth: usize)->Vec<(Vec<char>, usize)>{
+    gattaca_strings().into_iter().map(|item| (item, max_depth)).collect()
+}
+
+/// The GATTACA strings with a different truncation depth each, one of them untruncated.
+fn mixed_trunc_gattaca_set()->Vec<(Vec<char>, usize)>{
+    gattaca_strings().into_iter().zip([0, 5, 3]).collect()
+}
+
+/// Three DNA strings sharing some substrings, under the ids used by the comparison tests.
+fn related_strings()->Vec<(String, Vec<char>)>{
+    [("first", "GATTACAGATTACA"), ("second", "TTACAGGCAT"), ("third", "CCGATCGATT")].iter()
+        .map(|(id, string)| (id.to_string(), string.chars().collect()))
+        .collect()
+}
+
+/// Builds a tree from strings paired with their ids, truncating all of them at the same depth.
+fn build_named_tree(named_strings: &[(String, Vec<char>)], max_depth: usize)->KGST<char, String>{
+    let mut tree: KGST<char, String> = KGST::new('$');
+    for (id, string) in named_strings.iter(){
+        tree.insert(id.clone(), string.clone(), &max_depth);
+    }
+    tree
+}
+
+/// Returns the distinct substrings of the strings with a length in `lens`, in order of first appearance.
+fn distinct_substrings(string_set: &[(Vec<char>, usize)], lens: std::ops::RangeInclusive<usize>)->Vec<Vec<char>>{
+    string_set.iter()
+        .flat_map(|(item, _)| lens.clone().flat_map(move |len| item.windows(len).map(|window| window.to_vec())))
+        .unique()
+        .collect()
+}
+
+/// Converts the matches of a tree query to the sorted start positions returned by [`brute_force_occurrences`].
+fn sorted_matches<S: IntoIterator<Item = usize>>(matches: impl IntoIterator<Item = (String, S)>)->std::collections::HashMap<String, Vec<usize>>{
+    matches.into_iter().map(|(item_id, starts)| (item_id, starts.into_iter().sorted().collect())).collect()
+}
+
+/// Returns the total number of occurrences found by [`brute_force_occurrences`].
+fn num_occurrences(occurrences: &std::collections::HashMap<String, Vec<usize>>)->usize{
+    occurrences.values().map(|starts| starts.len()).sum()
+}
+
+/// Returns the k most frequent substrings with a length in `[min_len, max_len]` and their counts, ordered like [`KGST::top_k_substrings`].
+fn brute_force_top_k(string_set: &[(Vec<char>, usize)], k: usize, min_len: usize, max_len: usize, frequency: Frequency)->Vec<(usize, Vec<char>)>{
+    distinct_substrings(string_set, min_len..=max_len).into_iter()
+        .map(|substring| {
+            let occurrences = brute_force_occurrences(string_set, &substring);
+            match frequency{
+                Frequency::Occurrences => (num_occurrences(&occurrences), substring),
+                Frequency::Strings => (occurrences.len(), substring),
+            }
+        })
+        .filter(|(count, _)| *count>0)
+        .sorted_by(|a, b| b.0.cmp(&a.0).then(a.1.len().cmp(&b.1.len())).then(a.1.cmp(&b.1)))
+        .take(k)
+        .collect()
+}
+
+/// Returns the length of the shortest unique substring starting at every position of every string, within its truncation depth.
+fn brute_force_shortest_unique(string_set: &[(Vec<char>, usize)], uniqueness: Uniqueness)->Vec<Vec<Option<usize>>>{
+    string_set.iter().enumerate().map(|(item_idx, (item, max_depth))| {
+        (0..item.len()).map(|start| {
+            let longest = match max_depth {0 => item.len()-start, _ => (item.len()-start).min(*max_depth)};
+            (1..=longest).find(|len| {
+                let occurrences = brute_force_occurrences(string_set, &item[start..start+len]);
+                match uniqueness{
+                    Uniqueness::Collection => num_occurrences(&occurrences)==1,
+                    Uniqueness::String => occurrences[&item_idx.to_string()].len()==1,
+                }
+            })
+        }).collect()
+    }).collect()
+}
+
+/// Returns the minimal distinguishing substrings with their target and background counts and the targets containing them, ordered like [`KGST::distinguishing_substrings`].
+fn brute_force_distinguishing(string_set: &[(Vec<char>, usize)], targets: &[String], background: &[String], min_target_fraction: f64, max_background: usize)->Vec<(Vec<char>, usize, usize, Vec<String>)>{
+    let containing = |group: &[String], word: &[char]| brute_force_occurrences(string_set, word).into_keys().filter(|item_id| group.contains(item_id)).sorted().collect_vec();
+    let qualifies = |word: &[char]| !word.is_empty() && containing(targets, word).len() as f64>=min_target_fraction*targets.len() as f64 && containing(background, word).len()<=max_background;
+    distinct_substrings(string_set, 1..=string_set.iter().map(|(item, _)| item.len()).max().unwrap_or(0)).into_iter()
+        .filter(|word| qualifies(word) && !qualifies(&word[1..]) && !qualifies(&word[..word.len()-1]))
+        .map(|word| (word.clone(), containing(targets, &word).len(), containing(background, &word).len(), containing(targets, &word)))
+        .sorted_by(|a, b| b.1.cmp(&a.1).then(a.0.len().cmp(&b.0.len())).then(a.0.cmp(&b.0)))
+        .collect()
+}
+
+/// Draws small strings over a three letter alphabet, each with a random truncation depth.
+fn random_mixed_trunc_strings(rng: &mut StdRng)->Vec<(Vec<char>, usize)>{
+    (0..rng.gen_range(1..5))
+        .map(|_| ((0..rng.gen_range(1..9)).map(|_| ['a', 'b', 'c'][rng.gen_range(0..3)]).collect(), rng.gen_range(0..5)))
+        .collect()
+}
 
 #[test]
 fn create_tree() {
@@ -87,21 +222,39 @@ fn node_paths(){
     dbg!(edge_post.collect::<Vec<usize>>());
 }
 
-// #[test]
-// fn export_all_nodes(){
-//     let mut tree: KGST<char, String> = KGST::new('$');
-//     let string_set: Vec<Vec<char>> = vec![
-//         "MKAILVVLLYTFTTADADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLENRHNGKLCKLRGVAPLHLGKCNIAGWILGNPECESLSTAGSWSYIVETSNPDNGTCYPGDFINYEELREQLSSVSSFEKFEIFPKTSSWPNHDTNRGVTAACPHDGAKSFYRNLLWLVKKEKENSYPMINKSYTNNKGKEVLVLWAIHHPATSADQQSLYQNANAYVFVGSSKYSKKFEPEIAARPKVRDQAGRMKYYWTLVEPGDKITFEATGNLVVPIYAFALKRNSGSGIIISDTSVHDCDTTCQTPNGAINTSLPFQNIHPVTIGECPKYVKSTKLRMATGLRNIPSIQSRGLFGAIAGFIEGGWTGMIDGWYGYHHQNEQGSGYAADLKSTQNAIDGITNKVNSVIEKMNTQFTAVGKEFNHLERRIENLNKKVDDGFLDIWTYNAELLVLLENERTLDYHDSNVKNLYEKVRSQLKNNAKEIGNGCFEFYHKCDDTCMESVKNGTYDYPKYSEEAKLNREEIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
-//         "MKAILVVLLYTFTTANADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLEDKHNGKLCKLRGVAPLHLGKCNIAGWILGNPECESLSTARSWSYIVETSNSDNGTCYPGDFINYEELREQLSSVSSFERFEIFPKTSSWPNHDSNKGVTAACPHAGAKSFYKNLVWLVKKEKENSYPKLNKTYINDKGKEVLVLWGIHHPPTTADQQSLYQNADAYVFVGTSRYSKKFKPEIAKRPKVRDQEGRMNYYWTLVEPGDKITFEATGNLVVPRYAFTMERNAGSGIIISDTPVHDCNTTCQTPEGAINTSLPFQNVHPITIGKCPKYVKSTKLRLATGLRNVPSIQSRGLFGAIAGFIEGGWTGMVDGWYGYHHQNEQGSGYAADLKSTQNAIDEITNKVNSVIEKMNTQFTAVGKEFNHLEKRIENLNKKVDDGFLDIWTYNAXLLVLLENERTLDYHDSNVKNLYEKVRTQLKNNAKEIGNGCFEFYHKCDNTCMESVKNGTYDYPKYSEEAKLNREKIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
-//         "MKAIIVVVLLYTFTTANADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLKNRHNGKLCKLRGVAPLHLGKCNIAGWLLGNPECEPLSTASSWAYIVETSNSDNGTCYPGDFINYEELTEHLSSVSSFERFEIFPKTNSWPNHDTNKGVTAACPHAGTNSFYRNGIWLVKKENIYPKKSKSYKNKKKKEVLVLWAIHHPSTSADQQSLYQNADAYVFVGSSRYSRKFEPEIATRPKVRDQAGRMNYYWTLVEPGDKITFEATGNLVAPRYAFALKRNSGSGIIISDTSVHDCDTTCQTPNGAINTSLPFQNIHPVTIGECPKYVKSTKLRMATGLRNIPSIQSRGLFGAIAGFIEGGWTGMIDGWYGYHHQNEQGSGYAADLKSTQNAIDGITNKVNSVIEKMNTQFTAVGKEFSHLERRIENLNKKVDDGFLDIWTYNAELLVLLENERTLDYHDSNVKNLYEKVRSQLKNNAKEIGNGCFEFYHKCDDMCMESVKNGTYDYPKYSEEAKLNREEIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
-//         "MKAIIVVYLLHLQTATYADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLENRHNGKLCKLRGVAPLHLGKCNIAGWLLGNPECESLSTASSWSYIVETSNSDNGTCYPGDFINYEELREQLSSVSSFERFEIFPKTSSWPNHDTNRGVTAACPHAGTNSFYRNLVWLVKKGNSYPKINKSYINNKEKEVLVLWAIHHPSTSADQQSLYQNADAYVFVGSSRYSKKFEPEIATRPKVRDQAGRMNYYWTLVEPGDKITFEATGNLVAPRYAFALKRNSGSGIIISDTSVHDCDTTCQTPNGAINTSLPFQNIHPVTIGECPKYVKSTKLRMATGLRNIPSIQSRGLFGAIAGFIEGGWTGMIDGWYGYHHQNEQGSGYAADLKSTQNAIDGITNKVNSVIEKMNTQFTAVGKEFSHLERRIENLNKKVDDGFLDIWTYNAELLVLLENERTLDYHDSNVKNLYEKVRSQLKNNAKEIGNGCFEFYHKCDDMCMESVKNGTYDYPKYSEEAKLNREGIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
-//     ];
-//     for item_idx in 0..string_set.len(){
-//         tree.insert(item_idx.to_string(), string_set[item_idx].clone(), &0);
-//     }
-//     let nodes = tree.export_all_nodes();
-//     serde_json::to_string_pretty(&nodes).expect("error in node vec");
-// }
+#[test]
+fn export_all_nodes(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    let string_set: Vec<Vec<char>> = vec![
+        "MKAILVVLLYTFTTADADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLENRHNGKLCKLRGVAPLHLGKCNIAGWILGNPECESLSTAGSWSYIVETSNPDNGTCYPGDFINYEELREQLSSVSSFEKFEIFPKTSSWPNHDTNRGVTAACPHDGAKSFYRNLLWLVKKEKENSYPMINKSYTNNKGKEVLVLWAIHHPATSADQQSLYQNANAYVFVGSSKYSKKFEPEIAARPKVRDQAGRMKYYWTLVEPGDKITFEATGNLVVPIYAFALKRNSGSGIIISDTSVHDCDTTCQTPNGAINTSLPFQNIHPVTIGECPKYVKSTKLRMATGLRNIPSIQSRGLFGAIAGFIEGGWTGMIDGWYGYHHQNEQGSGYAADLKSTQNAIDGITNKVNSVIEKMNTQFTAVGKEFNHLERRIENLNKKVDDGFLDIWTYNAELLVLLENERTLDYHDSNVKNLYEKVRSQLKNNAKEIGNGCFEFYHKCDDTCMESVKNGTYDYPKYSEEAKLNREEIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
+        "MKAILVVLLYTFTTANADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLEDKHNGKLCKLRGVAPLHLGKCNIAGWILGNPECESLSTARSWSYIVETSNSDNGTCYPGDFINYEELREQLSSVSSFERFEIFPKTSSWPNHDSNKGVTAACPHAGAKSFYKNLVWLVKKEKENSYPKLNKTYINDKGKEVLVLWGIHHPPTTADQQSLYQNADAYVFVGTSRYSKKFKPEIAKRPKVRDQEGRMNYYWTLVEPGDKITFEATGNLVVPRYAFTMERNAGSGIIISDTPVHDCNTTCQTPEGAINTSLPFQNVHPITIGKCPKYVKSTKLRLATGLRNVPSIQSRGLFGAIAGFIEGGWTGMVDGWYGYHHQNEQGSGYAADLKSTQNAIDEITNKVNSVIEKMNTQFTAVGKEFNHLEKRIENLNKKVDDGFLDIWTYNAXLLVLLENERTLDYHDSNVKNLYEKVRTQLKNNAKEIGNGCFEFYHKCDNTCMESVKNGTYDYPKYSEEAKLNREKIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
+        "MKAIIVVVLLYTFTTANADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLKNRHNGKLCKLRGVAPLHLGKCNIAGWLLGNPECEPLSTASSWAYIVETSNSDNGTCYPGDFINYEELTEHLSSVSSFERFEIFPKTNSWPNHDTNKGVTAACPHAGTNSFYRNGIWLVKKENIYPKKSKSYKNKKKKEVLVLWAIHHPSTSADQQSLYQNADAYVFVGSSRYSRKFEPEIATRPKVRDQAGRMNYYWTLVEPGDKITFEATGNLVAPRYAFALKRNSGSGIIISDTSVHDCDTTCQTPNGAINTSLPFQNIHPVTIGECPKYVKSTKLRMATGLRNIPSIQSRGLFGAIAGFIEGGWTGMIDGWYGYHHQNEQGSGYAADLKSTQNAIDGITNKVNSVIEKMNTQFTAVGKEFSHLERRIENLNKKVDDGFLDIWTYNAELLVLLENERTLDYHDSNVKNLYEKVRSQLKNNAKEIGNGCFEFYHKCDDMCMESVKNGTYDYPKYSEEAKLNREEIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
+        "MKAIIVVYLLHLQTATYADTLCIGYHANNSTDTVDTVLEKNVTVTHSVNLLENRHNGKLCKLRGVAPLHLGKCNIAGWLLGNPECESLSTASSWSYIVETSNSDNGTCYPGDFINYEELREQLSSVSSFERFEIFPKTSSWPNHDTNRGVTAACPHAGTNSFYRNLVWLVKKGNSYPKINKSYINNKEKEVLVLWAIHHPSTSADQQSLYQNADAYVFVGSSRYSKKFEPEIATRPKVRDQAGRMNYYWTLVEPGDKITFEATGNLVAPRYAFALKRNSGSGIIISDTSVHDCDTTCQTPNGAINTSLPFQNIHPVTIGECPKYVKSTKLRMATGLRNIPSIQSRGLFGAIAGFIEGGWTGMIDGWYGYHHQNEQGSGYAADLKSTQNAIDGITNKVNSVIEKMNTQFTAVGKEFSHLERRIENLNKKVDDGFLDIWTYNAELLVLLENERTLDYHDSNVKNLYEKVRSQLKNNAKEIGNGCFEFYHKCDDMCMESVKNGTYDYPKYSEEAKLNREGIDGVKLESTRIYQILAIYSTVASSLVLVVSLGAISFWMCSNGSLQCRICI".to_string().chars().collect(), 
+    ];
+    for (item_idx, item) in string_set.iter().enumerate(){
+        tree.insert(item_idx.to_string(), item.clone(), &0);
+    }
+    let nodes = tree.export_all_nodes();
+    serde_json::to_string_pretty(&nodes).expect("error in node vec");
+    assert_eq!(nodes.len(), tree.num_nodes());
+    assert_eq!(nodes[0].data(), &["0", "1", "2", "3"]);
+    assert_eq!(nodes[0].slink(), None);
+    for node in nodes.iter(){
+        for (symbol, child_id) in node.children(){
+            assert_eq!(&tree.get_node_label(child_id)[0].into_inner(), &Some(symbol));
+            assert!(nodes[*child_id].data().iter().all(|item_id| node.data().contains(item_id)));
+        }
+        assert_eq!(node.slink().is_some(), node.node_id()!=0 && !node.children().is_empty());
+    }
+
+    let mut jsonl: Vec<u8> = vec![];
+    tree.write_nodes_jsonl(&mut jsonl).unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8(jsonl).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), nodes.len());
+    for (line, node) in lines.iter().zip(nodes.iter()){
+        assert_eq!(line, &serde_json::to_value(node).unwrap());
+    }
+}
 
 
 #[test]
@@ -146,6 +299,1075 @@ fn insert_set_trunc(){
     }
 }
 
+#[test]
+fn insert_mixed_trunc(){
+    let string_set: Vec<(Vec<char>, usize)> = vec![
+        ("abaab".chars().collect(), 2),
+        ("aabab".chars().collect(), 0),
+        ("babaabb".chars().collect(), 3),
+        ("ab".chars().collect(), 1),
+        ("bbaab".chars().collect(), 4),
+    ];
+    for order in [vec![0, 1, 2, 3, 4], vec![4, 3, 2, 1, 0], vec![1, 3, 0, 4, 2]]{
+        let mut tree: KGST<char, String> = KGST::new('$');
+        for item_idx in order.iter(){
+            let (item, max_depth) = &string_set[*item_idx];
+            tree.insert(item_idx.to_string(), item.clone(), max_depth);
+        }
+        for query_len in 1..=5{
+            for query in (0..query_len).map(|_| ['a', 'b']).multi_cartesian_product(){
+                let substring_match = tree.substring_match(&query);
+                for (item_idx, (item, max_depth)) in string_set.iter().enumerate(){
+                    let starts: Vec<usize> = match *max_depth==0 || query_len<=*max_depth{
+                        true => (0..item.len()).filter(|start| item[*start..].starts_with(&query)).collect(),
+                        false => vec![],
+                    };
+                    let found: Vec<usize> = substring_match.get(&item_idx.to_string()).map(|starts| starts.iter().copied().sorted().collect()).unwrap_or_default();
+                    assert_eq!(found, starts);
+                }
+            }
+        }
+        let stored: Vec<(usize, usize)> = (0..tree.num_nodes())
+            .filter(|node_id| tree.get_node(node_id).is_leaf())
+            .flat_map(|leaf_id| tree.get_node_data(&leaf_id).iter().flat_map(|(string_id, starts)| starts.iter().map(|start| (*string_id, *start))))
+            .sorted()
+            .collect();
+        let suffixes: Vec<(usize, usize)> = tree.get_strings().iter()
+            .flat_map(|(string_id, (item, _))| (0..item.get_string().len()).map(|start| (*string_id, start)))
+            .sorted()
+            .collect();
+        assert_eq!(stored, suffixes);
+    }
+}
+
+#[test]
+fn substring_match_positions(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert("first".to_string(), "abcabxabcd".chars().collect(), &0);
+    tree.insert("second".to_string(), "xabcxab".chars().collect(), &0);
+    let substring_match = tree.substring_match(&"abc".chars().collect::<Vec<char>>());
+    assert_eq!(substring_match.get("first").unwrap().iter().copied().sorted().collect::<Vec<usize>>(), vec![0, 6]);
+    assert_eq!(substring_match.get("second").unwrap().iter().copied().sorted().collect::<Vec<usize>>(), vec![1]);
+    let substring_match = tree.substring_match(&"xab".chars().collect::<Vec<char>>());
+    assert_eq!(substring_match.get("first").unwrap().iter().copied().sorted().collect::<Vec<usize>>(), vec![5]);
+    assert_eq!(substring_match.get("second").unwrap().iter().copied().sorted().collect::<Vec<usize>>(), vec![0, 4]);
+    assert!(!tree.substring_match(&"abcx".chars().collect::<Vec<char>>()).contains_key("first"));
+    assert!(tree.substring_match(&"abd".chars().collect::<Vec<char>>()).is_empty());
+}
+
+#[test]
+fn pattern_match(){
+    let string_set = gattaca_set(0);
+    let tree = build_tree(&string_set[..2]);
+    let mut expected = brute_force_occurrences(&string_set[..2], &"GATTA".chars().collect_vec());
+    for (item_id, starts) in brute_force_occurrences(&string_set[..2], &"GATCG".chars().collect_vec()){
+        let merged = expected.entry(item_id).or_default();
+        merged.extend(starts);
+        merged.sort();
+    }
+    assert_eq!(sorted_matches(tree.pattern_match(&Pattern::from_iupac("GATYR").unwrap())), expected);
+    assert_eq!(sorted_matches(tree.pattern_match(&Pattern::exact(&"GATTA".chars().collect_vec()).unwrap())), brute_force_occurrences(&string_set[..2], &"GATTA".chars().collect_vec()));
+    assert_eq!(sorted_matches(tree.pattern_match(&Pattern::from_iupac("GATTAN").unwrap())).get("1").unwrap(), &vec![6]);
+}
+
+#[test]
+fn pattern_parsing(){
+    let pattern = Pattern::from_prosite("C-x(2)-[ST]-{P}-H.").unwrap();
+    assert_eq!(pattern.len(), 6);
+    assert!(pattern.matches(&"CAASAH".chars().collect::<Vec<char>>()));
+    assert!(!pattern.matches(&"CAASPH".chars().collect::<Vec<char>>()));
+    assert!(Pattern::from_prosite("C-x(2,4)-H").is_err());
+    assert!(Pattern::from_iupac("ACGZ").is_err());
+    assert_eq!(Pattern::from_iupac("gatyr"), Pattern::from_iupac("GATYR"));
+
+    let pattern_str = "C-x(2)-[ST]-{P1}-H";
+    assert_eq!(Pattern::from_prosite(pattern_str), Err(PatternError::UnknownSymbol('1', 14)));
+    assert_eq!(pattern_str.chars().nth(14), Some('1'));
+    assert_eq!(Pattern::from_prosite(" C-2"), Err(PatternError::UnknownSymbol('2', 3)));
+    assert_eq!(GappedPattern::from_iupac("ACGT-x(2,5)-GGZ").unwrap_err(), PatternError::UnknownSymbol('Z', 14));
+    assert_eq!(Pattern::<char>::new(vec![]), Err(PatternError::Empty));
+    assert_eq!(Pattern::<char>::exact(&[]), Err(PatternError::Empty));
+    assert_eq!(Pattern::from_prosite("x(0)"), Err(PatternError::Empty));
+}
+
+#[test]
+fn gapped_match(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    let item_string: Vec<char> = "ACGTAAGGCTTACGTAAAAAAGGCACGTTGGCC".chars().collect();
+    tree.insert("World".to_string(), item_string.clone(), &6);
+    let pattern = GappedPattern::from_iupac("ACGT-x(2,6)-GGC").unwrap();
+    let mut expected: Vec<(usize, usize)> = vec![];
+    for start in 0..item_string.len()-3{
+        if item_string[start..start+4]!=['A', 'C', 'G', 'T']{
+            continue;
+        }
+        for gap in 2..=6{
+            let end = start+4+gap+3;
+            if end<=item_string.len() && item_string[end-3..end]==['G', 'G', 'C']{
+                expected.push((start, end));
+            }
+        }
+    }
+    assert_eq!(tree.gapped_match(&pattern).get("World").unwrap(), &expected);
+    assert_eq!(expected, vec![(0, 9), (11, 24)]);
+
+    let pattern = GappedPattern::from_prosite("C-x(2,4)-C-x(3)-H").unwrap();
+    assert_eq!(pattern.blocks().len(), 2);
+    assert_eq!(pattern.gaps(), &[(2, 4)]);
+    assert_eq!(pattern.span_bounds(), (8, 10));
+    assert!(GappedPattern::from_iupac("x(2,5)-ACGT").is_err());
+    assert!(GappedPattern::from_iupac("ACGT-x(5,2)-GGC").is_err());
+}
+
+#[test]
+fn count_occurrences(){
+    for string_set in [gattaca_set(0), mixed_trunc_gattaca_set()]{
+        let tree = build_tree(&string_set);
+        for query in distinct_substrings(&string_set, 1..=6){
+            let expected = brute_force_occurrences(&string_set, &query);
+            assert_eq!(tree.count_occurrences(&query), num_occurrences(&expected));
+            assert_eq!(tree.count_strings(&query), expected.len());
+        }
+        assert_eq!(tree.count_occurrences(&"GATTACAT".chars().collect::<Vec<char>>()), 0);
+    }
+    let mut tree = build_tree(&gattaca_set(0));
+    tree.insert("3".to_string(), "GATTACA".chars().collect(), &0);
+    assert_eq!(tree.count_strings(&"GATTACA".chars().collect::<Vec<char>>()), 2);
+}
+
+#[test]
+fn count_occurrences_mixed_trunc(){
+    let string_set: Vec<(Vec<char>, usize)> = vec![("abcab".chars().collect(), 2), ("abcabd".chars().collect(), 0)];
+    let tree = build_tree(&string_set);
+    assert_eq!(tree.count_occurrences(&['b', 'c', 'a']), 1);
+    assert_eq!(tree.count_strings(&['b', 'c', 'a']), 1);
+    assert_eq!(tree.count_occurrences(&['a', 'b']), 4);
+    assert_eq!(tree.count_strings(&['a', 'b']), 2);
+    for len in 1..=6{
+        for query in (0..len).map(|_| ['a', 'b', 'c', 'd']).multi_cartesian_product(){
+            let expected = brute_force_occurrences(&string_set, &query);
+            assert_eq!(tree.count_occurrences(&query), num_occurrences(&expected));
+            assert_eq!(tree.count_strings(&query), expected.len());
+        }
+    }
+}
+
+#[test]
+fn count_occurrences_random_mixed_trunc(){
+    let mut rng = StdRng::seed_from_u64(28);
+    for _ in 0..300{
+        let string_set = random_mixed_trunc_strings(&mut rng);
+        let tree = build_tree(&string_set);
+        for len in 1..=4{
+            for query in (0..len).map(|_| ['a', 'b', 'c']).multi_cartesian_product(){
+                let expected = brute_force_occurrences(&string_set, &query);
+                assert_eq!(tree.count_occurrences(&query), num_occurrences(&expected));
+                assert_eq!(tree.count_strings(&query), expected.len());
+            }
+        }
+    }
+}
+
+#[test]
+fn strings_containing(){
+    let mut string_set = gattaca_set(4);
+    string_set.push(("GATTACA".chars().collect(), 4));
+    for string_set in [string_set, mixed_trunc_gattaca_set()]{
+        let tree = build_tree(&string_set);
+        for query in distinct_substrings(&string_set, 1..=6){
+            assert_eq!(tree.strings_containing(&query), brute_force_occurrences(&string_set, &query).into_keys().collect());
+        }
+    }
+    assert!(build_tree(&gattaca_set(4)).strings_containing(&"GATTA".chars().collect::<Vec<char>>()).is_empty());
+}
+
+#[test]
+fn strings_containing_mixed_trunc(){
+    let string_set: Vec<(Vec<char>, usize)> = vec![("abcab".chars().collect(), 2), ("abcabd".chars().collect(), 0), ("cabca".chars().collect(), 3)];
+    let tree = build_tree(&string_set);
+    assert_eq!(tree.strings_containing(&['b', 'c', 'a']), ["1".to_string(), "2".to_string()].into_iter().collect());
+    assert_eq!(tree.strings_containing(&['a', 'b', 'c', 'a']), ["1".to_string()].into_iter().collect());
+    for len in 1..=6{
+        for query in (0..len).map(|_| ['a', 'b', 'c', 'd']).multi_cartesian_product(){
+            assert_eq!(tree.strings_containing(&query), brute_force_occurrences(&string_set, &query).into_keys().collect());
+        }
+    }
+}
+
+#[test]
+fn top_k_substrings(){
+    for string_set in [gattaca_set(0), mixed_trunc_gattaca_set()]{
+        let tree = build_tree(&string_set);
+        for (k, frequency) in [(10, Frequency::Occurrences), (2, Frequency::Strings)]{
+            let top = tree.top_k_substrings(k, 3, 5, frequency);
+            assert_eq!(top.iter().map(|x| (x.count, x.substring.clone())).collect::<Vec<(usize, Vec<char>)>>(), brute_force_top_k(&string_set, k, 3, 5, frequency));
+            for frequent_substring in top.iter(){
+                assert_eq!(frequent_substring.strings, brute_force_occurrences(&string_set, &frequent_substring.substring).into_keys().sorted().collect_vec());
+            }
+        }
+    }
+}
+
+#[test]
+fn top_k_substrings_mixed_trunc(){
+    let tree = build_tree(&[("abcab".chars().collect(), 2), ("abcabd".chars().collect(), 0)]);
+    let top = tree.top_k_substrings(10, 3, 3, Frequency::Occurrences);
+    assert_eq!(top.len(), 4);
+    assert!(top.iter().all(|substring| substring.count==1));
+
+    let mut rng = StdRng::seed_from_u64(30);
+    for _ in 0..200{
+        let string_set = random_mixed_trunc_strings(&mut rng);
+        let tree = build_tree(&string_set);
+        let top = tree.top_k_substrings(5, 2, 4, Frequency::Occurrences);
+        assert_eq!(top.iter().map(|x| (x.count, x.substring.clone())).collect::<Vec<(usize, Vec<char>)>>(), brute_force_top_k(&string_set, 5, 2, 4, Frequency::Occurrences));
+    }
+}
+
+#[test]
+fn kmers(){
+    for string_set in [gattaca_set(5), mixed_trunc_gattaca_set()]{
+        let tree = build_tree(&string_set);
+        for k in 1..=6{
+            let expected: std::collections::HashMap<Vec<char>, std::collections::HashMap<String, Vec<usize>>> = distinct_substrings(&string_set, k..=k).into_iter()
+                .map(|kmer| (brute_force_occurrences(&string_set, &kmer), kmer))
+                .filter(|(occurrences, _)| !occurrences.is_empty())
+                .map(|(occurrences, kmer)| (kmer, occurrences))
+                .collect();
+            let kmers: Vec<KmerCount<char, String>> = tree.iter_kmers(k).collect();
+            assert_eq!(kmers.len(), expected.len());
+            for kmer_count in kmers{
+                let occurrences = expected.get(&kmer_count.kmer).expect("k-mer not found!");
+                assert_eq!(kmer_count.count, num_occurrences(occurrences));
+                assert_eq!(kmer_count.string_counts, occurrences.iter().map(|(item_id, starts)| (item_id.clone(), starts.len())).collect());
+            }
+            let mut spectrum: std::collections::BTreeMap<usize, usize> = std::collections::BTreeMap::new();
+            for occurrences in expected.values(){
+                *spectrum.entry(num_occurrences(occurrences)).or_default() += 1;
+            }
+            assert_eq!(tree.kmer_spectrum(k), spectrum);
+        }
+    }
+    assert_eq!(build_tree(&gattaca_set(5)).iter_kmers(6).count(), 0);
+}
+
+#[test]
+fn kmers_mixed_trunc(){
+    let tree = build_tree(&[("abcab".chars().collect(), 2), ("abcabd".chars().collect(), 0)]);
+    assert_eq!(tree.kmer_spectrum(3).into_iter().collect::<Vec<(usize, usize)>>(), vec![(1, 4)]);
+    assert!(tree.iter_kmers(3).all(|kmer| kmer.count==1 && kmer.string_counts.keys().collect::<Vec<&String>>()==vec!["1"]));
+
+    let mut rng = StdRng::seed_from_u64(31);
+    for _ in 0..200{
+        let string_set = random_mixed_trunc_strings(&mut rng);
+        let tree = build_tree(&string_set);
+        for k in 1..=4{
+            let expected: std::collections::HashMap<Vec<char>, std::collections::HashMap<String, Vec<usize>>> = distinct_substrings(&string_set, k..=k).into_iter()
+                .map(|kmer| (brute_force_occurrences(&string_set, &kmer), kmer))
+                .filter(|(occurrences, _)| !occurrences.is_empty())
+                .map(|(occurrences, kmer)| (kmer, occurrences))
+                .collect();
+            let kmers: Vec<KmerCount<char, String>> = tree.iter_kmers(k).collect();
+            assert_eq!(kmers.len(), expected.len());
+            for kmer_count in kmers{
+                let occurrences = expected.get(&kmer_count.kmer).expect("k-mer not found!");
+                assert_eq!(kmer_count.count, num_occurrences(occurrences));
+                assert_eq!(kmer_count.string_counts, occurrences.iter().map(|(item_id, starts)| (item_id.clone(), starts.len())).collect());
+            }
+            let mut spectrum: std::collections::BTreeMap<usize, usize> = std::collections::BTreeMap::new();
+            for occurrences in expected.values(){
+                *spectrum.entry(num_occurrences(occurrences)).or_default() += 1;
+            }
+            assert_eq!(tree.kmer_spectrum(k), spectrum);
+        }
+    }
+}
+
+#[test]
+fn shortest_unique_substrings(){
+    for string_set in [gattaca_set(0), gattaca_set(4), mixed_trunc_gattaca_set()]{
+        let tree = build_tree(&string_set);
+        for uniqueness in [Uniqueness::Collection, Uniqueness::String]{
+            let sus = tree.shortest_unique_substrings(uniqueness);
+            for (item_idx, expected) in brute_force_shortest_unique(&string_set, uniqueness).iter().enumerate(){
+                assert_eq!(sus.get(&item_idx.to_string()).unwrap(), expected);
+            }
+        }
+    }
+    let tree = build_tree(&[("abcab".chars().collect(), 0), ("bcbca".chars().collect(), 0)]);
+    let global = tree.globally_shortest_unique_substrings(Uniqueness::Collection);
+    assert_eq!(global, vec![UniqueSubstring { string: "1".to_string(), start: 1, len: 2 }]);
+}
+
+#[test]
+fn shortest_unique_substrings_mixed_trunc(){
+    let mut rng = StdRng::seed_from_u64(32);
+    for _ in 0..300{
+        let string_set = random_mixed_trunc_strings(&mut rng);
+        let tree = build_tree(&string_set);
+        for uniqueness in [Uniqueness::Collection, Uniqueness::String]{
+            let sus = tree.shortest_unique_substrings(uniqueness);
+            for (item_idx, expected) in brute_force_shortest_unique(&string_set, uniqueness).iter().enumerate(){
+                assert_eq!(sus.get(&item_idx.to_string()).unwrap(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn minimal_absent_words(){
+    let string_set = gattaca_strings();
+    let alphabet: Vec<char> = string_set.iter().flatten().cloned().unique().sorted().collect();
+    let occurs = |haystack: &[&Vec<char>], word: &[char]| word.is_empty() || haystack.iter().any(|string| string.windows(word.len()).any(|w| w==word));
+    let brute_force = |haystack: &[&Vec<char>], max_len: usize| {
+        let mut words: Vec<Vec<char>> = vec![];
+        for len in 1..=max_len{
+            for word in (0..len).map(|_| alphabet.iter().cloned()).multi_cartesian_product(){
+                if !occurs(haystack, &word) && occurs(haystack, &word[..len-1]) && occurs(haystack, &word[1..]){
+                    words.push(word);
+                }
+            }
+        }
+        words
+    };
+    for max_depth in [0, 4]{
+        let tree = build_tree(&gattaca_set(max_depth));
+        let max_len = match max_depth {0 => 5, _ => max_depth};
+        assert_eq!(tree.minimal_absent_words(5), brute_force(&string_set.iter().collect_vec(), max_len));
+        let string_words = tree.string_minimal_absent_words(5);
+        for (item_idx, item) in string_set.iter().enumerate(){
+            assert_eq!(string_words.get(&item_idx.to_string()).unwrap(), &brute_force(&[item], max_len));
+        }
+    }
+}
+
+#[test]
+fn minimal_absent_words_mixed_lengths(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert("first".to_string(), "abaab".chars().collect(), &0);
+    let words: Vec<Vec<char>> = vec!["bb".chars().collect(), "aaa".chars().collect(), "bab".chars().collect(), "aaba".chars().collect()];
+    assert_eq!(tree.minimal_absent_words(5), words);
+    tree.insert("second".to_string(), "a".chars().collect(), &0);
+    assert_eq!(tree.minimal_absent_words(5), words);
+    assert_eq!(tree.string_minimal_absent_words(5).get("second").unwrap(), &vec![vec!['b'], vec!['a', 'a']]);
+    tree.insert("third".to_string(), "bbab".chars().collect(), &2);
+    assert_eq!(tree.minimal_absent_words(5), Vec::<Vec<char>>::new());
+    let string_words = tree.string_minimal_absent_words(5);
+    assert_eq!(string_words.get("first").unwrap(), &words);
+    assert_eq!(string_words.get("third").unwrap(), &vec![vec!['a', 'a']]);
+}
+
+#[test]
+fn distinguishing_substrings(){
+    let mut strings = gattaca_strings();
+    strings[2].extend("GGA".chars());
+    strings.extend(["GGATCCATTAC", "ACAGATCGG"].iter().map(|string| string.chars().collect::<Vec<char>>()));
+    let targets: Vec<String> = vec!["0".to_string(), "1".to_string(), "2".to_string()];
+    let background: Vec<String> = vec!["3".to_string(), "4".to_string()];
+    for depths in [[0; 5], [4; 5], [0, 4, 3, 0, 5]]{
+        let string_set: Vec<(Vec<char>, usize)> = strings.iter().cloned().zip(depths).collect();
+        let tree = build_tree(&string_set);
+        for (min_target_fraction, max_background) in [(1.0, 0), (0.6, 0), (0.6, 1), (0.3, 1)]{
+            let found = tree.distinguishing_substrings(&targets, &background, min_target_fraction, max_background).into_iter()
+                .map(|distinguishing| (distinguishing.substring, distinguishing.target_count, distinguishing.background_count, distinguishing.targets))
+                .collect_vec();
+            assert_eq!(found, brute_force_distinguishing(&string_set, &targets, &background, min_target_fraction, max_background));
+        }
+    }
+}
+
+#[test]
+fn distinguishing_substrings_mixed_trunc(){
+    let mut rng = StdRng::seed_from_u64(34);
+    for _ in 0..300{
+        let string_set = random_mixed_trunc_strings(&mut rng);
+        let tree = build_tree(&string_set);
+        let (targets, background): (Vec<String>, Vec<String>) = (0..string_set.len()).map(|idx| idx.to_string()).partition(|_| rng.gen_bool(0.5));
+        if targets.is_empty(){
+            continue;
+        }
+        for (min_target_fraction, max_background) in [(1.0, 0), (0.5, 1)]{
+            let found = tree.distinguishing_substrings(&targets, &background, min_target_fraction, max_background).into_iter()
+                .map(|distinguishing| (distinguishing.substring, distinguishing.target_count, distinguishing.background_count, distinguishing.targets))
+                .collect_vec();
+            assert_eq!(found, brute_force_distinguishing(&string_set, &targets, &background, min_target_fraction, max_background));
+        }
+    }
+}
+
+#[test]
+fn design_candidates(){
+    let mut string_set: Vec<(Vec<char>, usize)> = gattaca_set(0);
+    for (item, suffix) in string_set.iter_mut().zip(["GGCATTAC", "CAGGATCCGG", "GGATCCGGA"]){
+        item.0.extend(suffix.chars());
+    }
+    string_set.push(("GGATCCATTAC".chars().collect(), 0));
+    let tree = build_tree(&string_set);
+    let constraints = DesignConstraints { min_len: 5, max_len: 9, min_gc: 0.3, max_gc: 0.7, min_tm: 14.0, max_tm: 28.0, max_homopolymer: 2 };
+    let expected: Vec<String> = distinct_substrings(&string_set[..1], constraints.min_len..=constraints.max_len).into_iter()
+        .filter(|word| brute_force_occurrences(&string_set, word).into_keys().sorted().collect_vec()==["0", "1", "2"])
+        .filter(|word| (constraints.min_gc..=constraints.max_gc).contains(&gc_content(word)) && (constraints.min_tm..=constraints.max_tm).contains(&melting_temperature(word)) && longest_homopolymer(word)<=constraints.max_homopolymer)
+        .map(|word| word.iter().collect::<String>())
+        .sorted()
+        .collect();
+    let candidates = tree.design_candidates(&["0".to_string(), "1".to_string(), "2".to_string()], &["3".to_string()], &constraints);
+    assert!(!candidates.is_empty());
+    assert_eq!(candidates.iter().map(|candidate| candidate.sequence.clone()).sorted().collect_vec(), expected);
+    for window in candidates.windows(2){
+        assert!((window[0].melting_temperature-21.0).abs()<=(window[1].melting_temperature-21.0).abs());
+    }
+    assert!(candidates.iter().all(|candidate| candidate.targets==vec!["0".to_string(), "1".to_string(), "2".to_string()]));
+    assert_eq!(tree.design_candidates(&[], &["3".to_string()], &constraints), candidates);
+}
+
+#[test]
+fn design_candidates_strands(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert("target".to_string(), "AAGGTCCAGGTCCA".chars().collect(), &0);
+    tree.insert("other".to_string(), "TTGGTCCATT".chars().collect(), &0);
+    tree.insert("off_target".to_string(), "TTCCTGGTT".chars().collect(), &0);
+    let constraints = DesignConstraints { min_len: 5, max_len: 5, min_gc: 0.0, max_gc: 1.0, min_tm: 0.0, max_tm: 100.0, max_homopolymer: 2 };
+    let candidates = tree.design_candidates(&["target".to_string()], &["off_target".to_string()], &constraints);
+    // CCAGG only occurs in the off-target as its reverse complement CCTGG.
+    let counts: Vec<(&str, usize)> = candidates.iter().map(|candidate| (candidate.sequence.as_str(), candidate.occurrences)).sorted().collect();
+    assert_eq!(counts, vec![("AAGGT", 1), ("AGGTC", 2), ("CAGGT", 1), ("GGTCC", 2), ("GTCCA", 2), ("TCCAG", 1)]);
+    assert!(candidates.iter().all(|candidate| candidate.targets==vec!["target".to_string()]));
+}
+
+#[test]
+fn tandem_repeats(){
+    let string_set: Vec<Vec<char>> = vec!["GATTACAGATTACAGGATCCAAAAACACACAGT".chars().collect(), "CCGATCGATTACCAGATTAGATTAGATTCC".chars().collect(), "TTTTTTTTTTACACACATTTT".chars().collect()];
+    let brute_force = |string: &Vec<char>, min_period: usize, max_period: usize, min_copies: usize| {
+        let mut repeats: Vec<(usize, usize, usize)> = vec![];
+        for period in min_period..=max_period.min(string.len()/2){
+            for start in 0..=string.len()-2*period{
+                if start>=1 && string[start-1]==string[start+period-1]{
+                    continue;
+                }
+                let len = (start..string.len()).take_while(|idx| *idx<period+start || string[*idx]==string[idx-period]).count();
+                let unit = &string[start..start+period];
+                let primitive = (1..period).filter(|d| period.is_multiple_of(*d)).all(|d| unit[d..]!=unit[..period-d]);
+                if len/period>=min_copies.max(2) && primitive{
+                    repeats.push((start, period, len/period));
+                }
+            }
+        }
+        repeats
+    };
+    for max_depth in [0, 3]{
+        let mut tree: KGST<char, String> = KGST::new('$');
+        for (item_idx, item) in string_set.iter().enumerate(){
+            tree.insert(item_idx.to_string(), item.clone(), &max_depth);
+        }
+        for (min_period, max_period, min_copies) in [(1, 0, 2), (2, 5, 2), (1, 3, 3)]{
+            let repeats = tree.tandem_repeats(min_period, max_period, min_copies);
+            for (item_idx, item) in string_set.iter().enumerate(){
+                let expected = brute_force(item, min_period, match max_period {0 => item.len(), _ => max_period}, min_copies).into_iter().sorted().collect_vec();
+                let found = repeats.iter().filter(|repeat| repeat.string==item_idx.to_string()).map(|repeat| (repeat.start, repeat.period, repeat.copies)).collect_vec();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+    let mut rng = StdRng::seed_from_u64(36);
+    for _ in 0..200{
+        let item: Vec<char> = (0..rng.gen_range(1..30)).map(|_| ['a', 'b'][rng.gen_range(0..2)]).collect();
+        let mut tree: KGST<char, String> = KGST::new('$');
+        tree.insert("random".to_string(), item.clone(), &0);
+        for (min_period, max_period, min_copies) in [(1, 0, 2), (2, 4, 2), (3, 0, 3)]{
+            let found = tree.tandem_repeats(min_period, max_period, min_copies).into_iter().map(|repeat| (repeat.start, repeat.period, repeat.copies)).collect_vec();
+            assert_eq!(found, brute_force(&item, min_period, match max_period {0 => item.len(), _ => max_period}, min_copies).into_iter().sorted().collect_vec());
+        }
+    }
+}
+
+#[test]
+fn stranded_match(){
+    let mut string_set = gattaca_strings();
+    string_set[2].extend("GGA".chars());
+    assert_eq!(reverse_complement(&"ACGTRYKMBDHVNacgtn-".chars().collect_vec()), "-nacgtNBDHVKMRYACGT".chars().collect_vec());
+    for max_depth in [0, 5]{
+        let mut tree: KGST<char, String> = KGST::new('$');
+        for (item_idx, item) in string_set.iter().enumerate(){
+            tree.insert_both_strands(item_idx.to_string(), item.clone(), &max_depth);
+        }
+        for query in ["GAT", "TAATC", "GGATCC", "TCTG", "A"]{
+            let query: Vec<char> = query.chars().collect();
+            let query_rc = reverse_complement(&query);
+            let matches = tree.stranded_match(&query);
+            for (item_idx, item) in string_set.iter().enumerate(){
+                let expected: Vec<(usize, Strand)> = (0..item.len()).filter(|_| max_depth==0 || query.len()<=max_depth).flat_map(|start| {
+                    let window = &item[start..(start+query.len()).min(item.len())];
+                    [(window==query.as_slice()).then_some((start, Strand::Forward)), (window==query_rc.as_slice()).then_some((start, Strand::Reverse))]
+                }).flatten().collect();
+                assert_eq!(matches.get(&item_idx.to_string()).cloned().unwrap_or_default(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn stranded_string_counts(){
+    let records: Vec<(&str, Vec<char>, bool)> = vec![
+        ("first", "GATTACA".chars().collect(), true),
+        ("second", "TTACAGG".chars().collect(), true),
+        ("third", "CCCCGG".chars().collect(), false),
+    ];
+    let mut tree: KGST<char, String> = KGST::new('$');
+    for (item_id, item, both_strands) in records.iter(){
+        match both_strands{
+            true => tree.insert_both_strands(item_id.to_string(), item.clone(), &0),
+            false => tree.insert(item_id.to_string(), item.clone(), &0),
+        }
+    }
+    let strands: Vec<(&str, Vec<char>)> = records.iter()
+        .flat_map(|(item_id, item, both_strands)| [Some((*item_id, item.clone())), both_strands.then(|| (*item_id, reverse_complement(item)))].into_iter().flatten())
+        .collect();
+    for query in strands.iter().flat_map(|(_, strand)| (1..=4).flat_map(move |len| strand.windows(len).map(|window| window.to_vec()))).unique(){
+        let containing: Vec<&str> = strands.iter()
+            .filter(|(_, strand)| strand.windows(query.len()).any(|window| window==query))
+            .map(|(item_id, _)| *item_id)
+            .unique()
+            .collect();
+        assert_eq!(tree.count_strings(&query), containing.len());
+    }
+    let top = tree.top_k_substrings(5, 2, 4, Frequency::Strings);
+    assert_eq!(top[0].count, 2);
+    assert!(top.iter().all(|substring| substring.count==substring.strings.len() && substring.strings.iter().all_unique()));
+    let markers = tree.distinguishing_substrings(&["first".to_string(), "second".to_string()], &["third".to_string()], 1.0, 0);
+    assert!(!markers.is_empty());
+    assert!(markers.iter().all(|marker| marker.target_count==2 && marker.targets==vec!["first".to_string(), "second".to_string()]));
+}
+
+#[test]
+fn inverted_repeats(){
+    let string_set: Vec<Vec<char>> = vec!["GATTACAGAATTCAGGATCCATGCAT".chars().collect(), "CCGATCGATCGGAAATTTCCAGATTA".chars().collect(), "ACGTTTACGTAAACGT".chars().collect()];
+    let brute_force = |string: &Vec<char>, reflection: Reflection, gaps: std::ops::RangeInclusive<usize>, min_arm: usize, inward_maximal: bool| {
+        let pairs = |c_1: char, c_2: char| match reflection {
+            Reflection::Reverse => c_1==c_2,
+            Reflection::ReverseComplement => reverse_complement(&[c_2])==vec![c_1],
+        };
+        let mut repeats: Vec<(usize, usize, usize)> = vec![];
+        for center in 1..string.len(){
+            for gap in gaps.clone().filter(|gap| center+gap<string.len()){
+                if inward_maximal && gap>=2 && pairs(string[center], string[center+gap-1]){
+                    continue;
+                }
+                let arm = (0..center.min(string.len()-center-gap)).take_while(|t| pairs(string[center-1-t], string[center+gap+t])).count();
+                if arm>=min_arm{
+                    repeats.push((center-arm, arm, gap));
+                }
+            }
+        }
+        repeats.into_iter().sorted_by_key(|(start, arm, gap)| (*start, *gap, *arm)).collect_vec()
+    };
+    for (max_depth, both_strands) in [(0, [false; 3]), (4, [false; 3]), (0, [true; 3]), (4, [true; 3]), (0, [false, true, false]), (0, [true, false, true])]{
+        let mut tree: KGST<char, String> = KGST::new('$');
+        for (item_idx, item) in string_set.iter().enumerate(){
+            match both_strands[item_idx]{
+                true => tree.insert_both_strands(item_idx.to_string(), item.clone(), &max_depth),
+                false => tree.insert(item_idx.to_string(), item.clone(), &max_depth),
+            }
+        }
+        for reflection in [Reflection::Reverse, Reflection::ReverseComplement]{
+            let palindromes = tree.maximal_palindromes(reflection, 4);
+            let repeats = tree.inverted_repeats(reflection, 3, 1, 5);
+            for (item_idx, item) in string_set.iter().enumerate(){
+                let gaps = match reflection {Reflection::Reverse => 0..=1, Reflection::ReverseComplement => 0..=0};
+                let expected = brute_force(item, reflection, gaps, 1, false).into_iter().filter(|(_, arm, gap)| 2*arm+gap>=4).collect_vec();
+                let found = palindromes.iter().filter(|palindrome| palindrome.string==item_idx.to_string()).map(|palindrome| (palindrome.start, palindrome.arm, palindrome.gap)).collect_vec();
+                assert_eq!(found, expected);
+                let expected = brute_force(item, reflection, 1..=5, 3, true);
+                let found = repeats.iter().filter(|repeat| repeat.string==item_idx.to_string()).map(|repeat| (repeat.start, repeat.arm, repeat.gap)).collect_vec();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn insert_circular(){
+    let string_set = gattaca_strings();
+    for max_depth in [0, 5]{
+        let mut tree: KGST<char, String> = KGST::new('$');
+        tree.insert("linear".to_string(), string_set[0].clone(), &max_depth);
+        for (item_idx, item) in string_set.iter().enumerate(){
+            tree.insert_circular(item_idx.to_string(), item.clone(), &max_depth);
+        }
+        for query in ["CCGA", "AGA", "ATTAC", "CGAT", "AGATT", "T", "AGATTACAG", "CAGATTACA"]{
+            let query: Vec<char> = query.chars().collect();
+            let matches = tree.substring_match(&query);
+            let mut num_occurrences = 0;
+            for (item_idx, item) in string_set.iter().enumerate(){
+                let expected: Vec<usize> = (0..item.len())
+                    .filter(|start| (max_depth==0 || query.len()<=max_depth) && query.len()<=item.len() && (0..query.len()).all(|t| item[(start+t)%item.len()]==query[t]))
+                    .collect();
+                num_occurrences += expected.len();
+                assert_eq!(sorted_matches(matches.clone()).remove(&item_idx.to_string()).unwrap_or_default(), expected);
+            }
+            let linear: Vec<usize> = (0..string_set[0].len()).filter(|start| (max_depth==0 || query.len()<=max_depth) && string_set[0][*start..].starts_with(&query)).collect();
+            num_occurrences += linear.len();
+            assert_eq!(matches.get("linear").map(|starts| starts.len()).unwrap_or_default(), linear.len());
+            assert_eq!(tree.count_occurrences(&query), num_occurrences);
+        }
+    }
+}
+
+#[test]
+fn insert_circular_string_analyses(){
+    let plasmid: Vec<char> = "TACGGA".chars().collect();
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert_circular("plasmid".to_string(), plasmid.clone(), &0);
+    assert_eq!(tree.get_strings()[&0].0.get_string().len(), plasmid.len()+1);
+    let circular_count = |word: &[char]| (0..plasmid.len()).filter(|start| (0..word.len()).all(|t| plasmid[(start+t)%plasmid.len()]==word[t])).count();
+    let sus = tree.shortest_unique_substrings(Uniqueness::Collection);
+    let expected: Vec<Option<usize>> = (0..plasmid.len())
+        .map(|start| (1..=plasmid.len()-start).find(|len| circular_count(&(0..*len).map(|t| plasmid[(start+t)%plasmid.len()]).collect_vec())==1))
+        .collect();
+    assert_eq!(sus.get("plasmid").unwrap(), &expected);
+
+    tree.insert("linear".to_string(), plasmid.clone(), &0);
+    let lengths = tree.average_common_substrings();
+    assert_eq!(lengths.get(0, 0), 3.5);
+    assert_eq!(lengths.get(1, 1), 3.5);
+    assert_eq!(lengths.get(1, 0), 3.5);
+    let distances = tree.acs_distances();
+    assert!(distances.get(0, 1).is_finite());
+    assert_eq!(distances.get(0, 1), distances.get(1, 0));
+
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert_circular("circle".to_string(), "abc".chars().collect(), &0);
+    assert_eq!(tree.substring_match(&['c', 'a', 'b']).get("circle").unwrap(), &[2].into_iter().collect());
+    assert!(tree.substring_match(&['a', 'b', 'c', 'a']).is_empty());
+    assert!(tree.substring_match(&['a', 'b', 'c', 'a', 'b']).is_empty());
+    assert_eq!(tree.count_occurrences(&['a', 'b', 'c', 'a']), 0);
+    for node_id in 1..tree.num_nodes(){
+        let path_label: String = tree.iter_path_pre(&node_id).skip(1).flat_map(|path_node| tree.get_node_label(&path_node).iter().filter_map(|c| c.into_inner().copied()).collect_vec()).collect();
+        assert!(path_label.len()<=3 && "abcab".contains(path_label.trim_end_matches('$')));
+    }
+}
+
+#[test]
+fn translated_match(){
+    let string_set: Vec<Vec<char>> = vec!["ATGGCCATTGTAATGGGCCGCTGAAAGGGTGCCCGATAG".chars().collect(), "CTATCGGGCACCCTTTCAGCGGCCCATTACAATGGCCAT".chars().collect(), "ATGNNNTGGTAACTAA".chars().collect()];
+    assert_eq!(GeneticCode::Standard.translate(&"ATGTGATGA".chars().collect_vec()), vec!['M', '*', '*']);
+    assert_eq!(GeneticCode::VertebrateMitochondrial.translate(&"ATGTGAAGA".chars().collect_vec()), vec!['M', 'W', '*']);
+    assert_eq!(GeneticCode::Standard.translate(&"CTNGCRAAY".chars().collect_vec()), vec!['L', 'A', 'N']);
+    assert_eq!(GeneticCode::Standard.translate(&"ATNGAR".chars().collect_vec()), vec!['X', 'E']);
+    for max_depth in [0, 4]{
+        let mut tree: KGST<char, String> = KGST::new('$');
+        for (item_idx, item) in string_set.iter().enumerate(){
+            tree.insert_translated(item_idx.to_string(), item.clone(), GeneticCode::Standard, &max_depth);
+        }
+        for query in ["MA", "MAIVMGR", "GAR", "W", "PL", "XW", "R", "A"]{
+            let query: Vec<char> = query.chars().collect();
+            let hits = tree.translated_match(&query);
+            assert_eq!(tree.count_strings(&query), hits.len());
+            for (item_idx, item) in string_set.iter().enumerate(){
+                let mut expected: Vec<TranslatedHit> = vec![];
+                for (strand, sequence) in [(Strand::Forward, item.clone()), (Strand::Reverse, reverse_complement(item))]{
+                    for offset in 0..3{
+                        let protein = GeneticCode::Standard.translate(&sequence[offset..]);
+                        for start in (0..protein.len()).filter(|start| (max_depth==0 || query.len()<=max_depth) && protein[*start..].starts_with(&query)){
+                            let (frame_start, frame_end) = (offset+3*start, offset+3*(start+query.len()));
+                            let (start, end) = match strand {Strand::Forward => (frame_start, frame_end), Strand::Reverse => (item.len()-frame_end, item.len()-frame_start)};
+                            assert_eq!(&item[start..end], &match strand {Strand::Forward => sequence[frame_start..frame_end].to_vec(), Strand::Reverse => reverse_complement(&sequence[frame_start..frame_end])}[..]);
+                            expected.push(TranslatedHit { start, end, frame: ReadingFrame { strand, offset } });
+                        }
+                    }
+                }
+                expected.sort();
+                assert_eq!(hits.get(&item_idx.to_string()).cloned().unwrap_or_default(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn to_dot(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert("first".to_string(), "GATTACAGATTACA".chars().collect(), &0);
+    tree.insert("second".to_string(), "TTACAGG".chars().collect(), &0);
+    let export = |options: &DotOptions| {
+        let mut dot: Vec<u8> = vec![];
+        tree.to_dot(&mut dot, options).unwrap();
+        String::from_utf8(dot).unwrap()
+    };
+
+    let dot = export(&DotOptions::default());
+    assert!(dot.starts_with("digraph KGST {\n") && dot.ends_with("}\n"));
+    let tree_edges = dot.lines().filter(|line| line.contains(" -> ") && !line.contains("dashed")).count();
+    assert_eq!(tree_edges, tree.num_nodes()-1);
+    assert!(dot.lines().any(|line| line.contains("dashed")));
+    assert_eq!(dot.matches("(first, ").count(), 15);
+    assert_eq!(dot.matches("(second, ").count(), 8);
+
+    let dot = export(&DotOptions { max_label_len: Some(2), suffix_links: false, leaf_data: false, ..Default::default() });
+    assert!(!dot.contains("dashed") && !dot.contains("(first, "));
+    assert!(dot.lines().filter(|line| line.contains("label=\"") && line.contains(" -> ")).all(|line| line.split('"').nth(1).unwrap().trim_end_matches("...").chars().count()<=2));
+
+    let node_id = (1..tree.num_nodes()).find(|node_id| !tree.get_node_label(node_id).is_empty() && tree.get_node_label(node_id)[0].into_inner()==Some(&'A')).unwrap();
+    let dot = export(&DotOptions { root: Some(node_id), max_depth: Some(1), ..Default::default() });
+    let nodes = dot.lines().filter(|line| line.ends_with("];") && !line.contains(" -> ") && !line.trim_start().starts_with("node")).count();
+    assert_eq!(nodes, 1+tree.get_nodes()[&node_id].get_children().len());
+    assert!(tree.to_dot(&mut vec![], &DotOptions { root: Some(tree.num_nodes()), ..Default::default() }).is_err());
+}
+
+#[test]
+fn write_network(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert("first".to_string(), "GATTACAGATTACAGG<&>".chars().collect(), &0);
+    let export = |format: NetworkFormat| {
+        let mut network: Vec<u8> = vec![];
+        tree.write_network(&mut network, format).unwrap();
+        String::from_utf8(network).unwrap()
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&export(NetworkFormat::NodeLinkJson)).unwrap();
+    let nodes = json["nodes"].as_array().unwrap();
+    let links = json["links"].as_array().unwrap();
+    assert_eq!(nodes.len(), tree.num_nodes());
+    let num_internal = nodes.iter().filter(|node| !node["leaf"].as_bool().unwrap()).count()-1;
+    assert_eq!(links.iter().filter(|link| link["type"]=="tree").count(), tree.num_nodes()-1);
+    assert_eq!(links.iter().filter(|link| link["type"]=="suffix_link").count(), num_internal);
+    assert_eq!(nodes[0]["occurrences"], 20);
+
+    let mut path_labels: Vec<String> = vec![String::new(); tree.num_nodes()];
+    let mut stack: Vec<usize> = vec![0];
+    while let Some(node_id) = stack.pop(){
+        for link in links.iter().filter(|link| link["type"]=="tree" && link["source"]==node_id){
+            let child_id = link["target"].as_u64().unwrap() as usize;
+            path_labels[child_id] = format!("{}{}", path_labels[node_id], link["label"].as_str().unwrap());
+            assert_eq!(nodes[child_id]["string_depth"].as_u64().unwrap() as usize, path_labels[child_id].chars().count());
+            stack.push(child_id);
+        }
+    }
+    for link in links.iter().filter(|link| link["type"]=="suffix_link"){
+        let (source, target) = (link["source"].as_u64().unwrap() as usize, link["target"].as_u64().unwrap() as usize);
+        assert_eq!(path_labels[source][1..], path_labels[target]);
+    }
+
+    let graphml = export(NetworkFormat::GraphML);
+    assert_eq!(graphml.matches("<node ").count(), tree.num_nodes());
+    assert_eq!(graphml.matches("<edge ").count(), links.len());
+    assert!(graphml.contains("&lt;&amp;&gt;") && !graphml.contains("<&>"));
+    let gexf = export(NetworkFormat::Gexf);
+    assert_eq!(gexf.matches("<node ").count(), tree.num_nodes());
+    assert_eq!(gexf.matches("<edge ").count(), links.len());
+}
+
+#[test]
+fn to_newick(){
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert("first".to_string(), "GATTACAGATTACA".chars().collect(), &0);
+    tree.insert("it's, second".to_string(), "TTACAGG".chars().collect(), &0);
+    let mut newick: Vec<u8> = vec![];
+    tree.to_newick(&mut newick, true).unwrap();
+    let newick = String::from_utf8(newick).unwrap();
+    assert!(newick.ends_with(";\n"));
+    assert_eq!(newick.matches('(').count(), newick.matches(')').count());
+    let num_leaves = tree.get_nodes().values().filter(|node| node.is_leaf()).count();
+    assert_eq!(newick.matches('(').count(), tree.num_nodes()-num_leaves);
+    assert_eq!(newick.matches(':').count(), tree.num_nodes()-1);
+    for start in 0..15{
+        assert!(newick.contains(&format!("first_{}", start)));
+    }
+    for start in 0..8{
+        assert!(newick.contains(&format!("'it''s, second_{}", start)) || newick.contains(&format!("|it''s, second_{}", start)));
+    }
+}
+
+#[test]
+fn incidence_matrix(){
+    let tree = build_named_tree(&related_strings(), 4);
+    let binary = tree.incidence_matrix(NodeWeight::Binary);
+    let occurrences = tree.incidence_matrix(NodeWeight::Occurrences);
+    let edge_lengths = tree.incidence_matrix(NodeWeight::EdgeLength);
+    assert_eq!(binary.row_ids(), &["first".to_string(), "second".to_string(), "third".to_string()]);
+    assert_eq!(binary.num_cols(), tree.num_nodes());
+    assert_eq!(binary.nnz(), occurrences.nnz());
+
+    for (row, string_id) in tree.get_strings().keys().sorted().enumerate(){
+        let mut expected: Vec<usize> = vec![0; tree.num_nodes()];
+        for leaf_id in (0..tree.num_nodes()).filter(|node_id| tree.get_node(node_id).is_leaf()){
+            if let Some(starts) = tree.get_node_data(&leaf_id).get(string_id){
+                for node_id in tree.iter_path_pre(&leaf_id){
+                    expected[node_id] += starts.len();
+                }
+            }
+        }
+        let (cols, values) = occurrences.row(row);
+        assert!(cols.windows(2).all(|pair| pair[0]<pair[1]));
+        assert_eq!(cols.iter().zip(values.iter()).map(|(col, value)| (*col, *value)).collect_vec(), expected.iter().enumerate().filter(|(_, count)| **count>0).map(|(node_id, count)| (node_id, *count)).collect_vec());
+        assert_eq!(binary.row(row).0, cols);
+        assert!(binary.row(row).1.iter().all(|value| *value==1));
+        let (edge_cols, edge_values) = edge_lengths.row(row);
+        assert_eq!(edge_cols, cols.iter().copied().filter(|col| tree.get_node(col).get_edge_length()>0).collect_vec());
+        assert!(edge_cols.iter().zip(edge_values.iter()).all(|(col, value)| tree.get_node(col).get_edge_length()==*value));
+    }
+
+    let mut mtx: Vec<u8> = vec![];
+    occurrences.write_matrix_market(&mut mtx).unwrap();
+    let mtx = String::from_utf8(mtx).unwrap();
+    assert_eq!(mtx.lines().count(), 2+occurrences.num_rows()+occurrences.nnz());
+    assert_eq!(mtx.lines().nth(4).unwrap(), format!("3 {} {}", tree.num_nodes(), occurrences.nnz()));
+    let mut csr: Vec<u8> = vec![];
+    occurrences.write_csr(&mut csr).unwrap();
+    let words = csr.chunks(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()) as usize).collect_vec();
+    assert_eq!(words.len(), 3+occurrences.num_rows()+1+2*occurrences.nnz());
+    assert_eq!(&words[3..7], occurrences.row_offsets());
+    assert_eq!(&words[7+occurrences.nnz()..], occurrences.values());
+}
+
+#[test]
+fn incidence_matrix_mixed_trunc(){
+    let mut rng = StdRng::seed_from_u64(45);
+    for _ in 0..200{
+        let string_set = random_mixed_trunc_strings(&mut rng);
+        let tree = build_tree(&string_set);
+        let terminated: Vec<(Vec<char>, usize)> = string_set.iter()
+            .map(|(item, max_depth)| (item.iter().copied().chain(['$']).collect(), *max_depth))
+            .collect();
+        let occurrences = tree.incidence_matrix(NodeWeight::Occurrences);
+        let binary = tree.incidence_matrix(NodeWeight::Binary);
+        let mut expected: Vec<Vec<(usize, usize)>> = vec![vec![]; string_set.len()];
+        for node_id in 0..tree.num_nodes(){
+            let path: Vec<usize> = match node_id {0 => vec![], _ => tree.iter_path_pre(&node_id).skip(1).collect()};
+            let path_label: Vec<char> = path.into_iter()
+                .flat_map(|path_node| tree.get_node_label(&path_node).iter().map(|c| c.into_inner().copied().unwrap_or('$')).collect_vec())
+                .collect();
+            for (item_id, starts) in brute_force_occurrences(&terminated, &path_label){
+                expected[item_id.parse::<usize>().unwrap()].push((node_id, starts.len()));
+            }
+        }
+        for (row, row_entries) in expected.iter().enumerate(){
+            let (cols, values) = occurrences.row(row);
+            assert_eq!(cols.iter().copied().zip(values.iter().copied()).collect_vec(), row_entries.clone());
+            assert_eq!(binary.row(row).0, cols);
+        }
+    }
+}
+
+#[test]
+fn similarity_matrix(){
+    let mut named_strings = related_strings();
+    named_strings.push(("fourth id".to_string(), "AAAA".chars().collect()));
+    let tree = build_named_tree(&named_strings, 0);
+    let (id_set, string_set): (Vec<String>, Vec<Vec<char>>) = named_strings.into_iter().unzip();
+    let substrings = |string: &Vec<char>| {
+        let string: Vec<char> = string.iter().copied().chain(['$']).collect();
+        (0..string.len()).flat_map(|start| (start+1..=string.len()).map(move |end| (start, end))).map(|(start, end)| string[start..end].iter().collect::<String>()).unique().collect_vec()
+    };
+    let similarities = tree.similarity_matrix(&[], Similarity::WeightedJaccard, NodeWeight::EdgeLength);
+    assert_eq!(similarities.ids(), id_set.as_slice());
+    for i in 0..id_set.len(){
+        for j in 0..id_set.len(){
+            let (a, b) = (substrings(&string_set[i]), substrings(&string_set[j]));
+            let shared = a.iter().filter(|substring| b.contains(substring)).count();
+            assert!((similarities.get(i, j)-shared as f64/(a.len()+b.len()-shared) as f64).abs()<1e-12);
+        }
+    }
+
+    let incidence = tree.incidence_matrix(NodeWeight::Occurrences);
+    let cosine = tree.similarity_matrix(&["third".to_string(), "missing".to_string(), "first".to_string()], Similarity::Cosine, NodeWeight::Occurrences);
+    assert_eq!(cosine.ids(), &["third".to_string(), "first".to_string()]);
+    let dense = |row: usize| {
+        let mut values: Vec<f64> = vec![0.0; incidence.num_cols()];
+        for (col, value) in incidence.row(row).0.iter().zip(incidence.row(row).1.iter()){
+            values[*col] = *value as f64;
+        }
+        values
+    };
+    let (a, b) = (dense(2), dense(0));
+    let dot: f64 = a.iter().zip(b.iter()).map(|(x, y)| x*y).sum();
+    let norm = |v: &Vec<f64>| v.iter().map(|x| x*x).sum::<f64>().sqrt();
+    assert!((cosine.get(0, 1)-dot/(norm(&a)*norm(&b))).abs()<1e-12);
+    assert_eq!(cosine.get(0, 1), cosine.get(1, 0));
+
+    let mut phylip: Vec<u8> = vec![];
+    similarities.to_distances().write_phylip(&mut phylip).unwrap();
+    let phylip = String::from_utf8(phylip).unwrap();
+    let lines = phylip.lines().collect_vec();
+    assert_eq!(lines[0], "4");
+    assert!(lines[1].starts_with("first      0.000000 "));
+    assert!(lines[4].starts_with("fourth_id  "));
+    assert_eq!(lines[4].split_whitespace().count(), 5);
+    let mut tsv: Vec<u8> = vec![];
+    similarities.write_tsv(&mut tsv).unwrap();
+    assert_eq!(String::from_utf8(tsv).unwrap().lines().next().unwrap(), "\tfirst\tsecond\tthird\tfourth id");
+}
+
+#[test]
+fn alignment_free_distances(){
+    let mut named_strings = related_strings();
+    named_strings.push(("fourth".to_string(), "ACGTTGCA".chars().collect()));
+    let string_set: Vec<Vec<char>> = named_strings.iter().map(|(_, string)| string.clone()).collect();
+    let kmers = |string: &Vec<char>, k: usize| string.windows(k).map(|kmer| kmer.to_vec()).collect_vec();
+    for max_depth in [0, 4]{
+        let tree = build_named_tree(&named_strings, max_depth);
+        let lengths = tree.average_common_substrings();
+        let cap = match max_depth{
+            0 => usize::MAX,
+            _ => max_depth,
+        };
+        for (i, a) in string_set.iter().enumerate(){
+            for (j, b) in string_set.iter().enumerate(){
+                let statistics: usize = (0..a.len()).map(|start| (0..=a.len()-start).rev().find(|len| *len==0 || b.windows(*len).any(|window| window==&a[start..start+len])).unwrap().min(cap)).sum();
+                assert_eq!(lengths.get(i, j), statistics as f64/a.len() as f64);
+            }
+        }
+    }
+
+    let tree = build_named_tree(&named_strings, 0);
+    let distances = tree.acs_distances();
+    let lengths = tree.average_common_substrings();
+    let directed = |i: usize, j: usize| (string_set[j].len() as f64).ln()/lengths.get(i, j)-(string_set[i].len() as f64).ln()/lengths.get(i, i);
+    assert!((distances.get(0, 2)-(directed(0, 2)+directed(2, 0))/2.0).abs()<1e-12);
+    assert_eq!(distances.get(1, 1), 0.0);
+
+    let alphabet = ['A', 'C', 'G', 'T'];
+    for (k, max_mismatches) in [(2, 0), (3, 0), (3, 1), (3, 2)]{
+        let spectrum = tree.spectrum_kernel(k);
+        let mismatch = tree.mismatch_kernel(k, max_mismatches);
+        let features = |string: &Vec<char>| (0..k).map(|_| alphabet.iter()).multi_cartesian_product()
+            .map(|neighbour| kmers(string, k).iter().filter(|kmer| kmer.iter().zip(neighbour.iter()).filter(|(x, y)| x!=*y).count()<=max_mismatches).count() as f64)
+            .collect_vec();
+        for (i, a) in string_set.iter().enumerate(){
+            for (j, b) in string_set.iter().enumerate(){
+                let expected: f64 = features(a).iter().zip(features(b).iter()).map(|(x, y)| x*y).sum();
+                assert_eq!(mismatch.get(i, j), expected);
+                if max_mismatches==0{
+                    assert_eq!(spectrum.get(i, j), expected);
+                }
+            }
+        }
+    }
+    let kernel_distances = tree.spectrum_kernel(3).kernel_distances();
+    assert_eq!(kernel_distances.get(2, 2), 0.0);
+    assert!(kernel_distances.get(0, 2)>0.0 && kernel_distances.get(0, 2)<=2f64.sqrt());
+
+    let mut tree: KGST<char, String> = KGST::new('$');
+    for (id, string) in ["A", "C", "A", "AC"].iter().enumerate(){
+        tree.insert(id.to_string(), string.chars().collect(), &0);
+    }
+    let distances = tree.acs_distances();
+    assert_eq!(distances.get(0, 1), f64::INFINITY);
+    assert_eq!(distances.get(0, 2), 0.0);
+    assert!((distances.get(0, 3)-((2f64.ln()/1.0-0.0)+(0.0-2f64.ln()/1.5))/2.0).abs()<1e-12);
+    assert!((0..4).all(|i| (0..4).all(|j| !distances.get(i, j).is_nan())));
+}
+
+#[test]
+fn feature_vectors(){
+    let tree = build_named_tree(&related_strings(), 0);
+    let (id_set, string_set): (Vec<String>, Vec<Vec<char>>) = related_strings().into_iter().unzip();
+    let vectors: FeatureVectors<char, String> = tree.feature_vectors(2, 4, 2);
+    assert_eq!(vectors.counts().row_ids(), id_set.as_slice());
+    assert_eq!(vectors.counts().num_cols(), vectors.features().len());
+    assert!(vectors.features().iter().all_unique());
+    assert!(vectors.features().contains(&vec!['G', 'A', 'T']));
+    let occurrences = |string: &Vec<char>, feature: &Vec<char>| string.windows(feature.len()).filter(|window| window==feature).count();
+    for (col, feature) in vectors.features().iter().enumerate(){
+        assert!((2..=4).contains(&feature.len()));
+        assert!(string_set.iter().filter(|string| occurrences(string, feature)>0).count()>=2);
+        for (row, string) in string_set.iter().enumerate(){
+            let (cols, counts) = vectors.counts().row(row);
+            let count = cols.iter().position(|c| *c==col).map(|idx| counts[idx]).unwrap_or(0);
+            assert_eq!(count, occurrences(string, feature));
+        }
+    }
+
+    let mut libsvm: Vec<u8> = vec![];
+    vectors.write_libsvm(&mut libsvm).unwrap();
+    let libsvm = String::from_utf8(libsvm).unwrap();
+    let lines = libsvm.lines().collect_vec();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("1 ") && lines[1].ends_with(" # second"));
+    assert_eq!(lines[2].split_whitespace().count(), 3+vectors.counts().row(2).0.len());
+    let mut dictionary: Vec<u8> = vec![];
+    vectors.write_features(&mut dictionary).unwrap();
+    assert_eq!(String::from_utf8(dictionary).unwrap().lines().count(), 1+vectors.features().len());
+}
+
+#[test]
+fn cluster(){
+    let mut string_set: Vec<Vec<char>> = related_strings().into_iter().map(|(_, string)| string).collect();
+    string_set.extend(["GGGGCCCC", "ACGTTGCA", "TTTTTTTT", "CGATCGA"].iter().map(|string| string.chars().collect::<Vec<char>>()));
+    let id_set: Vec<String> = (0..string_set.len()).map(|idx| format!("seq{}", idx)).collect();
+    let tree = build_named_tree(&id_set.iter().cloned().zip(string_set.iter().cloned()).collect_vec(), 0);
+    let kmers = |string: &Vec<char>, k: usize| string.windows(k).map(|kmer| kmer.to_vec()).unique().collect_vec();
+    let order = (0..string_set.len()).sorted_by_key(|idx| std::cmp::Reverse(string_set[*idx].len())).collect_vec();
+    let as_ids = |clusters: Vec<Vec<usize>>| clusters.into_iter().map(|members| members.into_iter().map(|idx| id_set[idx].clone()).collect_vec()).collect_vec();
+    for (k, min_fraction) in [(3, 0.0), (4, 0.0), (3, 0.5), (2, 0.6)]{
+        let shared = |a: usize, b: usize| {
+            let (kmers_a, kmers_b) = (kmers(&string_set[a], k), kmers(&string_set[b], k));
+            let count = kmers_a.iter().filter(|kmer| kmers_b.contains(kmer)).count();
+            (count, count>0 && count as f64>=min_fraction*kmers_a.len().min(kmers_b.len()) as f64)
+        };
+        let link = match min_fraction>0.0{
+            true => ClusterLink::SharedKmers { k, min_fraction },
+            false => ClusterLink::SharedSubstring(k),
+        };
+
+        let mut components: Vec<usize> = (0..string_set.len()).collect();
+        for _ in 0..string_set.len(){
+            for (a, b) in (0..string_set.len()).tuple_combinations(){
+                if shared(a, b).1{
+                    let component = components[a].min(components[b]);
+                    (components[a], components[b]) = (component, component);
+                }
+            }
+        }
+        let expected = order.iter().map(|idx| components[*idx]).unique().map(|component| (0..string_set.len()).filter(|idx| components[*idx]==component).collect_vec()).collect_vec();
+        let clusters = tree.cluster(link, ClusterMethod::SingleLinkage);
+        assert_eq!(clusters.iter().map(|cluster| cluster.members.clone()).collect_vec(), as_ids(expected));
+        assert!(clusters.iter().all(|cluster| cluster.members.contains(&cluster.representative)));
+
+        let mut centroids: Vec<usize> = vec![];
+        let mut assignments: Vec<usize> = (0..string_set.len()).collect();
+        for idx in order.iter(){
+            match centroids.iter().filter(|centroid| shared(*idx, **centroid).1).max_by_key(|centroid| (shared(*idx, **centroid).0, std::cmp::Reverse(order.iter().position(|other| other==*centroid)))){
+                Some(centroid) => assignments[*idx] = *centroid,
+                None => centroids.push(*idx),
+            }
+        }
+        let clusters = tree.cluster(link, ClusterMethod::GreedyCentroid);
+        assert_eq!(clusters.iter().map(|cluster| cluster.representative.clone()).collect_vec(), centroids.iter().map(|idx| id_set[*idx].clone()).collect_vec());
+        assert_eq!(clusters.iter().map(|cluster| cluster.members.clone()).collect_vec(), as_ids(centroids.iter().map(|centroid| (0..string_set.len()).filter(|idx| assignments[*idx]==*centroid).collect_vec()).collect_vec()));
+    }
+}
+
+#[test]
+fn containment_report(){
+    let string_set: Vec<&str> = vec!["GATTACAGATTACA", "TTACA", "CAGGCAT", "GATTACAGATTACA", "TTACA", "ACAGA", "CCGATCGATT", "GATT", "CAGGCAT"];
+    let id_set: Vec<String> = (0..string_set.len()).map(|idx| format!("seq{}", idx)).collect();
+    let tree = build_named_tree(&id_set.iter().cloned().zip(string_set.iter().map(|string| string.chars().collect())).collect_vec(), 0);
+    let report: ContainmentReport<String> = tree.containment_report();
+    assert_eq!(report.duplicates, vec![
+        vec!["seq0".to_string(), "seq3".to_string()],
+        vec!["seq1".to_string(), "seq4".to_string()],
+        vec!["seq2".to_string(), "seq8".to_string()],
+    ]);
+    let mut expected: Vec<(String, String, usize)> = vec![];
+    for (i, a) in string_set.iter().enumerate(){
+        for (j, b) in string_set.iter().enumerate(){
+            if a.len()<b.len(){
+                if let Some(offset) = b.find(a){
+                    expected.push((id_set[i].clone(), id_set[j].clone(), offset));
+                }
+            }
+        }
+    }
+    assert_eq!(report.contained, expected);
+    assert_eq!(report.redundant_ids().into_iter().sorted().collect_vec(), vec!["seq1", "seq3", "seq4", "seq5", "seq7", "seq8"]);
+
+    let mut tree: KGST<char, String> = KGST::new('$');
+    tree.insert_both_strands("first".to_string(), "GATTACA".chars().collect(), &0);
+    tree.insert("second".to_string(), "TGTAA".chars().collect(), &0);
+    tree.insert("third".to_string(), "TTAC".chars().collect(), &4);
+    let report = tree.containment_report();
+    assert!(report.duplicates.is_empty() && report.contained.is_empty());
+}
+
 // #[test]
 // fn insert_set_var_trunc(){
 //     let mut tree: KGST<char, String> = KGST::new('$');